@@ -0,0 +1,84 @@
+use std::{fs::File, io::BufReader, process::exit};
+
+use chess_engine::{book_builder::BookBuilder, pgn::PgnReader};
+
+const USAGE: &str =
+    "usage: build_book [--ply N] [--min-games N] [--min-score F] -o book.bin games.pgn...";
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("{} expects a number\n{}", flag, USAGE);
+            exit(1)
+        }
+    }
+}
+
+fn main() {
+    let mut max_ply = 20;
+    let mut min_games = 3;
+    let mut min_score = 0.0;
+    let mut output = None;
+    let mut pgn_paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ply" => max_ply = parse_value(&arg, args.next()),
+            "--min-games" => min_games = parse_value(&arg, args.next()),
+            "--min-score" => min_score = parse_value(&arg, args.next()),
+            "-o" | "--output" => output = args.next(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => pgn_paths.push(arg),
+        }
+    }
+
+    let Some(output) = output else {
+        eprintln!("no output file given\n{}", USAGE);
+        exit(1)
+    };
+
+    if pgn_paths.is_empty() {
+        eprintln!("no pgn files given\n{}", USAGE);
+        exit(1)
+    }
+
+    let mut book_builder = BookBuilder::new(max_ply, min_games, min_score);
+    let mut added = 0;
+    let mut skipped = 0;
+
+    for pgn_path in &pgn_paths {
+        let file = match File::open(pgn_path) {
+            Ok(file) => file,
+            Err(error) => {
+                eprintln!("could not open {}: {}", pgn_path, error);
+                exit(1)
+            }
+        };
+
+        for game in PgnReader::new(BufReader::new(file)) {
+            if book_builder.add_game(&game) {
+                added += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+    }
+
+    let entries = match book_builder.write(&output) {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("could not write {}: {}", output, error);
+            exit(1)
+        }
+    };
+
+    println!(
+        "{} games added, {} skipped, {} entries written to {}",
+        added, skipped, entries, output
+    );
+}
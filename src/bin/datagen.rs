@@ -0,0 +1,273 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    process::exit,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chess_engine::{
+    nnue::Network,
+    pgn::GameResult,
+    player::{Color, PieceType},
+    position::Position,
+    search::{tt::TranspositionTable, SearchLimits, Searcher},
+    training_data::{write_binary, write_text, TrainingEntry},
+};
+use macroquad::rand::{gen_range, srand};
+
+const USAGE: &str = "usage: datagen [--games N] [--threads N] [--depth N] [--random-plies N] \
+[--hash MB] [--format text|binary] [--nnue network.bin] -o output";
+
+// a side this far ahead is taken to have won, saves playing out dead lost games
+const ADJUDICATE_SCORE: i32 = 2500;
+const MAX_GAME_PLIES: usize = 400;
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("{} expects a number\n{}", flag, USAGE);
+            exit(1)
+        }
+    }
+}
+
+// nothing but kings and at most one minor piece between them
+fn is_insufficient_material(position: &Position) -> bool {
+    let white = &position.white_player;
+    let black = &position.black_player;
+
+    let minors = (white.knight_bitboard
+        | white.bishop_bitboard
+        | black.knight_bitboard
+        | black.bishop_bitboard)
+        .count_ones();
+    let others = [PieceType::Pawn, PieceType::Rook, PieceType::Queen]
+        .into_iter()
+        .any(|piece_type| white.piece_bitboard(piece_type) | black.piece_bitboard(piece_type) != 0);
+
+    !others && minors <= 1
+}
+
+fn game_over(position: &Position) -> Option<GameResult> {
+    if position.legal_moves().is_empty() {
+        return Some(match (position.in_check(), position.side_to_move) {
+            (true, Color::White) => GameResult::BlackWin,
+            (true, Color::Black) => GameResult::WhiteWin,
+            (false, _) => GameResult::Draw,
+        });
+    }
+
+    if position.halfmove_clock >= 100
+        || position.is_repetition()
+        || is_insufficient_material(position)
+    {
+        return Some(GameResult::Draw);
+    }
+
+    None
+}
+
+// random moves from the start so games dont all follow the same line, None if the game ended on the way
+fn random_opening(random_plies: usize) -> Option<Position> {
+    let mut position = Position::new();
+
+    for _ in 0..random_plies {
+        let moves = position.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+        position.make_move(moves[gen_range(0, moves.len())]);
+    }
+
+    game_over(&position).is_none().then_some(position)
+}
+
+struct Settings {
+    depth: u32,
+    random_plies: usize,
+    network: Option<Arc<Network>>,
+}
+
+// quiet positions of one game, all labelled with its result once known
+fn play_game(settings: &Settings, searcher: &mut Searcher) -> Vec<TrainingEntry> {
+    let mut position = loop {
+        if let Some(position) = random_opening(settings.random_plies) {
+            break position;
+        }
+    };
+    if let Some(network) = &settings.network {
+        position.enable_nnue(network.clone());
+    }
+
+    // each game starts from nothing, the way a fresh engine would
+    searcher.new_game();
+
+    let mut entries = Vec::new();
+    let limits = SearchLimits {
+        depth: Some(settings.depth),
+        ..SearchLimits::default()
+    };
+
+    let result = loop {
+        if let Some(result) = game_over(&position) {
+            break result;
+        }
+        if position.history.len() >= MAX_GAME_PLIES {
+            break GameResult::Draw;
+        }
+
+        let result = searcher.search(&mut position, &limits, |_| {});
+        let (best_move, score) = (result.best_move.unwrap(), result.score);
+        let white_score = match position.side_to_move {
+            Color::White => score,
+            Color::Black => -score,
+        };
+
+        if white_score.abs() >= ADJUDICATE_SCORE {
+            break if white_score > 0 {
+                GameResult::WhiteWin
+            } else {
+                GameResult::BlackWin
+            };
+        }
+
+        // the static eval of a position where something is hanging or a check has to be answered means little
+        if !position.in_check() && !position.is_capture(best_move) && best_move.promotion.is_none()
+        {
+            let mut recorded = position.clone();
+            recorded.disable_nnue();
+            recorded.history.clear();
+
+            entries.push(TrainingEntry {
+                position: recorded,
+                score: white_score as i16,
+                result: GameResult::Unknown,
+            });
+        }
+
+        position.make_move(best_move);
+    };
+
+    for entry in &mut entries {
+        entry.result = result;
+    }
+
+    entries
+}
+
+fn main() {
+    let mut games = 100;
+    let mut threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut depth = 4;
+    let mut hash = TranspositionTable::DEFAULT_MEGABYTES;
+    let mut random_plies = 8;
+    let mut binary = false;
+    let mut network_path = None;
+    let mut output = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => games = parse_value(&arg, args.next()),
+            "--threads" => threads = parse_value::<usize>(&arg, args.next()).max(1),
+            "--depth" => depth = parse_value::<u32>(&arg, args.next()).max(1),
+            "--hash" => hash = parse_value(&arg, args.next()),
+            "--random-plies" => random_plies = parse_value(&arg, args.next()),
+            "--format" => match args.next().as_deref() {
+                Some("text") => binary = false,
+                Some("binary") => binary = true,
+                _ => {
+                    eprintln!("--format expects text or binary\n{}", USAGE);
+                    exit(1)
+                }
+            },
+            "--nnue" => network_path = args.next(),
+            "-o" | "--output" => output = args.next(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => {
+                eprintln!("unknown argument {}\n{}", arg, USAGE);
+                exit(1)
+            }
+        }
+    }
+
+    let Some(output) = output else {
+        eprintln!("no output file given\n{}", USAGE);
+        exit(1)
+    };
+
+    let network = network_path.map(|path| match Network::load(&path) {
+        Ok(network) => Arc::new(network),
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(1)
+        }
+    });
+
+    let writer = match File::create(&output) {
+        Ok(file) => Mutex::new(BufWriter::new(file)),
+        Err(error) => {
+            eprintln!("could not create {}: {}", output, error);
+            exit(1)
+        }
+    };
+
+    srand(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64),
+    );
+
+    let settings = Settings {
+        depth,
+        random_plies,
+        network,
+    };
+    let games_started = AtomicUsize::new(0);
+    let positions_written = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                // a table per thread, nothing ever stops a datagen search early
+                let mut searcher = Searcher::new(
+                    Arc::new(TranspositionTable::new(hash)),
+                    Arc::new(AtomicBool::new(false)),
+                );
+
+                while games_started.fetch_add(1, Ordering::Relaxed) < games {
+                    let entries = play_game(&settings, &mut searcher);
+
+                    let mut writer = writer.lock().unwrap();
+                    let written = if binary {
+                        write_binary(&mut *writer, &entries)
+                    } else {
+                        write_text(&mut *writer, &entries)
+                    };
+                    if let Err(error) = written {
+                        eprintln!("could not write {}: {}", output, error);
+                        exit(1)
+                    }
+
+                    let total = positions_written.fetch_add(entries.len(), Ordering::Relaxed)
+                        + entries.len();
+                    println!("game finished, {} positions written", total);
+                }
+            });
+        }
+    });
+
+    if let Err(error) = writer.into_inner().unwrap().flush() {
+        eprintln!("could not write {}: {}", output, error);
+        exit(1)
+    }
+}
@@ -0,0 +1,57 @@
+use std::{process::exit, sync::Arc};
+
+use chess_engine::{
+    eval::trace::eval_trace,
+    nnue::Network,
+    position::{Position, STARTING_FEN},
+};
+
+const USAGE: &str = "usage: eval_trace [--nnue network.bin] [fen]";
+
+fn main() {
+    let mut network_path = None;
+    let mut args = Vec::new();
+
+    let mut arg_iter = std::env::args().skip(1);
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--nnue" => network_path = arg_iter.next(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => args.push(arg),
+        }
+    }
+
+    // the fen can be given as one quoted argument or split over several
+    let fen = if args.is_empty() {
+        STARTING_FEN.to_owned()
+    } else {
+        args.join(" ")
+    };
+
+    let mut position = match Position::from_fen(&fen) {
+        Ok(position) => position,
+        Err(error) => {
+            eprintln!("invalid fen: {}\n{}", error, USAGE);
+            exit(1)
+        }
+    };
+
+    println!("{}", eval_trace(&position));
+
+    // the network has no terms to break down, just its verdict next to the handcrafted one
+    if let Some(network_path) = network_path {
+        match Network::load(&network_path) {
+            Ok(network) => {
+                position.enable_nnue(Arc::new(network));
+                println!("nnue {:+}", position.nnue_eval().unwrap());
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1)
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    process::exit,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use chess_engine::{
+    nnue::Network,
+    position::{Position, STARTING_FEN},
+    search::{
+        format_score, selectivity::Selectivity, smp::ThreadPool, time::TimeControl,
+        tt::TranspositionTable, SearchLimits, SearchResult,
+    },
+};
+
+const USAGE: &str =
+    "usage: search [--depth N] [--nodes N] [--movetime MS] [--mate N] [--infinite] \
+[--wtime MS] [--btime MS] [--winc MS] [--binc MS] [--movestogo N] [--searchmoves move,move...] \
+[--multipv N] [--hash MB] [--threads N] [--nnue network.bin] [--disable technique]... [fen]";
+
+// without any limits given the search goes this deep
+const DEFAULT_DEPTH: u32 = 6;
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("{} expects a number\n{}", flag, USAGE);
+            exit(1)
+        }
+    }
+}
+
+fn main() {
+    let mut limits = SearchLimits::default();
+    let mut search_moves = None;
+    let mut hash = TranspositionTable::DEFAULT_MEGABYTES;
+    let mut threads = 1;
+    let mut multi_pv = 1;
+    let mut network_path = None;
+    let mut selectivity = Selectivity::default();
+    let mut args = Vec::new();
+
+    let mut arg_iter = std::env::args().skip(1);
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--depth" => limits.depth = Some(parse_value(&arg, arg_iter.next())),
+            "--nodes" => limits.nodes = Some(parse_value(&arg, arg_iter.next())),
+            "--movetime" => {
+                limits.move_time = Some(Duration::from_millis(parse_value(&arg, arg_iter.next())))
+            }
+            "--mate" => limits.mate = Some(parse_value(&arg, arg_iter.next())),
+            // runs until enter is pressed
+            "--infinite" => limits.infinite = true,
+            "--wtime" | "--btime" | "--winc" | "--binc" => {
+                let time = Duration::from_millis(parse_value(&arg, arg_iter.next()));
+                let control = limits.time_control.get_or_insert_with(TimeControl::default);
+                match arg.as_str() {
+                    "--wtime" => control.white_time = time,
+                    "--btime" => control.black_time = time,
+                    "--winc" => control.white_increment = time,
+                    _ => control.black_increment = time,
+                }
+            }
+            "--movestogo" => {
+                limits
+                    .time_control
+                    .get_or_insert_with(TimeControl::default)
+                    .moves_to_go = Some(parse_value(&arg, arg_iter.next()))
+            }
+            // checked against the position once it is known
+            "--searchmoves" => search_moves = arg_iter.next(),
+            "--hash" => hash = parse_value(&arg, arg_iter.next()),
+            "--threads" => threads = parse_value::<usize>(&arg, arg_iter.next()).max(1),
+            "--multipv" => multi_pv = parse_value::<usize>(&arg, arg_iter.next()).max(1),
+            "--nnue" => network_path = arg_iter.next(),
+            // for testing what each pruning, reduction or extension is worth
+            "--disable" => {
+                let name = arg_iter.next().unwrap_or_default();
+                if let Err(error) = selectivity.set(&name, false) {
+                    eprintln!("{}\n{}", error, USAGE);
+                    exit(1)
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => args.push(arg),
+        }
+    }
+
+    // the fen can be given as one quoted argument or split over several
+    let fen = if args.is_empty() {
+        STARTING_FEN.to_owned()
+    } else {
+        args.join(" ")
+    };
+
+    let mut position = match Position::from_fen(&fen) {
+        Ok(position) => position,
+        Err(error) => {
+            eprintln!("invalid fen: {}\n{}", error, USAGE);
+            exit(1)
+        }
+    };
+
+    for uci in search_moves
+        .iter()
+        .flat_map(|search_moves| search_moves.split(','))
+    {
+        match position.move_from_uci(uci) {
+            Some(search_move) => limits.search_moves.push(search_move),
+            None => {
+                eprintln!("{} is not a legal move here\n{}", uci, USAGE);
+                exit(1)
+            }
+        }
+    }
+
+    let limited = limits.depth.is_some()
+        || limits.nodes.is_some()
+        || limits.move_time.is_some()
+        || limits.time_control.is_some()
+        || limits.mate.is_some();
+    if !limited && !limits.infinite {
+        limits.depth = Some(DEFAULT_DEPTH);
+    }
+
+    if let Some(network_path) = network_path {
+        match Network::load(&network_path) {
+            Ok(network) => position.enable_nnue(Arc::new(network)),
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1)
+            }
+        }
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut pool = ThreadPool::new(
+        threads,
+        Arc::new(TranspositionTable::new(hash)),
+        stop.clone(),
+    );
+    pool.selectivity = selectivity;
+    pool.multi_pv = multi_pv;
+
+    if limits.infinite {
+        thread::spawn(move || {
+            let _ = std::io::stdin().read_line(&mut String::new());
+            stop.store(true, Ordering::Relaxed);
+        });
+    }
+
+    let start = Instant::now();
+    let chess960 = position.chess960;
+    let report = |result: &SearchResult| {
+        let elapsed = start.elapsed();
+
+        for (index, line) in result.lines.iter().enumerate() {
+            let pv = line
+                .pv
+                .iter()
+                .map(|pv_move| pv_move.to_uci(chess960))
+                .collect::<Vec<String>>()
+                .join(" ");
+            // numbered the way uci does it, and only once there is more than one line
+            let multi_pv = if multi_pv > 1 {
+                format!(" multipv {}", index + 1)
+            } else {
+                String::new()
+            };
+
+            println!(
+                "depth {}{} score {} nodes {} nps {} time {} pv {}",
+                result.depth,
+                multi_pv,
+                format_score(line.score),
+                result.nodes,
+                (result.nodes as f64 / elapsed.as_secs_f64().max(1e-3)) as u64,
+                elapsed.as_millis(),
+                pv
+            );
+        }
+    };
+
+    let result = pool.search(&position, &limits, report);
+
+    match result.best_move {
+        Some(best_move) => println!("bestmove {}", best_move.to_uci(chess960)),
+        None => println!("no legal moves"),
+    }
+}
@@ -0,0 +1,370 @@
+use std::{fs::File, io::BufReader, process::exit};
+
+use chess_engine::{
+    nnue::{
+        for_each_feature, Network, HIDDEN_SIZE, INPUT_SIZE, MAX_OUTPUT_BIAS, OUTPUT_SCALE, QA, QB,
+    },
+    player::Color,
+    training_data::{read_binary, read_text, result_value, TrainingEntry},
+};
+use macroquad::rand::gen_range;
+
+const USAGE: &str = "usage: train_nnue [--epochs N] [--batch-size N] [--lr F] [--wdl F] \
+[--optimizer adam|sgd] -o network.bin data...";
+
+// the largest float weights that still fit in i16 once quantised, with room left for the accumulator
+const MAX_FEATURE_WEIGHT: f32 = 1.98;
+const MAX_OUTPUT_WEIGHT: f32 = i16::MAX as f32 / QB as f32;
+// the loader turns down networks with a bigger bias
+const MAX_OUTPUT_BIAS_FLOAT: f32 = MAX_OUTPUT_BIAS as f32 / (QA * QB) as f32;
+
+const ADAM_BETA1: f32 = 0.9;
+const ADAM_BETA2: f32 = 0.999;
+const ADAM_EPSILON: f32 = 1e-8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Optimizer {
+    Sgd,
+    Adam,
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("{} expects a number\n{}", flag, USAGE);
+            exit(1)
+        }
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[derive(Clone, Copy)]
+struct StepSettings {
+    optimizer: Optimizer,
+    lr: f32,
+    scale: f32,
+    time_step: i32,
+}
+
+// one block of weights with what the optimiser needs to remember about it
+struct Parameter {
+    values: Vec<f32>,
+    gradients: Vec<f32>,
+    momentum: Vec<f32>,
+    velocity: Vec<f32>,
+    limit: f32,
+}
+
+impl Parameter {
+    fn new(size: usize, spread: f32, limit: f32) -> Self {
+        Parameter {
+            values: (0..size)
+                .map(|_| {
+                    if spread > 0.0 {
+                        gen_range(-spread, spread)
+                    } else {
+                        0.0
+                    }
+                })
+                .collect(),
+            gradients: vec![0.0; size],
+            momentum: vec![0.0; size],
+            velocity: vec![0.0; size],
+            limit,
+        }
+    }
+
+    // applies and clears the gradient of values[start..end], scale turns the summed gradient into a mean
+    fn step(&mut self, start: usize, end: usize, settings: &StepSettings) {
+        let StepSettings {
+            optimizer,
+            lr,
+            scale,
+            time_step,
+        } = *settings;
+        let bias_correction1 = 1.0 - ADAM_BETA1.powi(time_step);
+        let bias_correction2 = 1.0 - ADAM_BETA2.powi(time_step);
+
+        for index in start..end {
+            let gradient = self.gradients[index] * scale;
+            self.gradients[index] = 0.0;
+
+            let update = match optimizer {
+                Optimizer::Sgd => gradient,
+                Optimizer::Adam => {
+                    self.momentum[index] =
+                        ADAM_BETA1 * self.momentum[index] + (1.0 - ADAM_BETA1) * gradient;
+                    self.velocity[index] = ADAM_BETA2 * self.velocity[index]
+                        + (1.0 - ADAM_BETA2) * gradient * gradient;
+
+                    let momentum = self.momentum[index] / bias_correction1;
+                    let velocity = self.velocity[index] / bias_correction2;
+                    momentum / (velocity.sqrt() + ADAM_EPSILON)
+                }
+            };
+
+            self.values[index] = (self.values[index] - lr * update).clamp(-self.limit, self.limit);
+        }
+    }
+}
+
+// the float version of the engine network, trained here then rounded into the engines format
+struct Trainer {
+    feature_weights: Parameter,
+    feature_biases: Parameter,
+    output_weights: Parameter,
+    output_bias: Parameter,
+    // rows of feature_weights with a gradient waiting, only those get stepped
+    touched: Vec<bool>,
+    touched_features: Vec<usize>,
+    time_step: i32,
+}
+
+impl Trainer {
+    fn new() -> Self {
+        Trainer {
+            feature_weights: Parameter::new(INPUT_SIZE * HIDDEN_SIZE, 0.1, MAX_FEATURE_WEIGHT),
+            feature_biases: Parameter::new(HIDDEN_SIZE, 0.0, MAX_FEATURE_WEIGHT),
+            output_weights: Parameter::new(2 * HIDDEN_SIZE, 0.1, MAX_OUTPUT_WEIGHT),
+            output_bias: Parameter::new(1, 0.0, MAX_OUTPUT_BIAS_FLOAT),
+            touched: vec![false; INPUT_SIZE],
+            touched_features: Vec::new(),
+            time_step: 0,
+        }
+    }
+
+    // the trainers target, a blend of the search score and the game result, both from the side to move
+    fn target(entry: &TrainingEntry, wdl: f32) -> f32 {
+        let (score, result) = match entry.position.side_to_move {
+            Color::White => (entry.score as f32, result_value(entry.result)),
+            Color::Black => (-entry.score as f32, 1.0 - result_value(entry.result)),
+        };
+
+        wdl * result + (1.0 - wdl) * sigmoid(score / OUTPUT_SCALE as f32)
+    }
+
+    // runs one entry forward and backward, adds to the gradients and returns the loss
+    fn train_entry(&mut self, entry: &TrainingEntry, wdl: f32) -> f32 {
+        let side_to_move = entry.position.side_to_move;
+        let perspectives = [side_to_move, side_to_move.opposite_color()];
+
+        let mut features = [Vec::new(), Vec::new()];
+        let mut accumulators = [[0.0; HIDDEN_SIZE]; 2];
+
+        for (side, perspective) in perspectives.into_iter().enumerate() {
+            accumulators[side].copy_from_slice(&self.feature_biases.values);
+            for_each_feature(&entry.position, perspective, |feature| {
+                features[side].push(feature);
+                let row = &self.feature_weights.values[feature * HIDDEN_SIZE..][..HIDDEN_SIZE];
+                for (value, weight) in accumulators[side].iter_mut().zip(row) {
+                    *value += weight;
+                }
+            });
+        }
+
+        // flattened the accumulators line up with the output weights, side to move half first
+        let output = self.output_bias.values[0]
+            + accumulators
+                .iter()
+                .flatten()
+                .zip(&self.output_weights.values)
+                .map(|(value, weight)| value.clamp(0.0, 1.0) * weight)
+                .sum::<f32>();
+
+        let prediction = sigmoid(output);
+        let error = prediction - Self::target(entry, wdl);
+        let output_gradient = 2.0 * error * prediction * (1.0 - prediction);
+
+        self.output_bias.gradients[0] += output_gradient;
+
+        for (side, accumulator) in accumulators.iter().enumerate() {
+            for (hidden, &accumulator) in accumulator.iter().enumerate() {
+                let output_index = side * HIDDEN_SIZE + hidden;
+
+                self.output_weights.gradients[output_index] +=
+                    output_gradient * accumulator.clamp(0.0, 1.0);
+
+                // the clipped relu passes nothing back outside of (0, 1)
+                if accumulator > 0.0 && accumulator < 1.0 {
+                    let hidden_gradient =
+                        output_gradient * self.output_weights.values[output_index];
+
+                    self.feature_biases.gradients[hidden] += hidden_gradient;
+                    for feature in &features[side] {
+                        self.feature_weights.gradients[feature * HIDDEN_SIZE + hidden] +=
+                            hidden_gradient;
+                    }
+                }
+            }
+
+            for &feature in &features[side] {
+                if !self.touched[feature] {
+                    self.touched[feature] = true;
+                    self.touched_features.push(feature);
+                }
+            }
+        }
+
+        error * error
+    }
+
+    fn step(&mut self, optimizer: Optimizer, lr: f32, batch_size: usize) {
+        self.time_step += 1;
+        let settings = StepSettings {
+            optimizer,
+            lr,
+            scale: 1.0 / batch_size as f32,
+            time_step: self.time_step,
+        };
+
+        // features that were never on in this batch keep their adam state untouched, sparse but much faster
+        for feature in self.touched_features.drain(..) {
+            self.touched[feature] = false;
+            self.feature_weights.step(
+                feature * HIDDEN_SIZE,
+                (feature + 1) * HIDDEN_SIZE,
+                &settings,
+            );
+        }
+
+        self.feature_biases.step(0, HIDDEN_SIZE, &settings);
+        self.output_weights.step(0, 2 * HIDDEN_SIZE, &settings);
+        self.output_bias.step(0, 1, &settings);
+    }
+
+    fn quantise(&self) -> Network {
+        let round = |value: f32, scale: i32| (value * scale as f32).round() as i16;
+
+        Network {
+            feature_weights: self
+                .feature_weights
+                .values
+                .iter()
+                .map(|value| round(*value, QA))
+                .collect(),
+            feature_biases: self
+                .feature_biases
+                .values
+                .iter()
+                .map(|value| round(*value, QA))
+                .collect(),
+            output_weights: self
+                .output_weights
+                .values
+                .iter()
+                .map(|value| round(*value, QB))
+                .collect(),
+            output_bias: ((self.output_bias.values[0] * (QA * QB) as f32).round() as i32)
+                .clamp(-MAX_OUTPUT_BIAS, MAX_OUTPUT_BIAS),
+        }
+    }
+}
+
+fn load_entries(path: &str) -> Vec<TrainingEntry> {
+    let file = match File::open(path) {
+        Ok(file) => BufReader::new(file),
+        Err(error) => {
+            eprintln!("could not open {}: {}", path, error);
+            exit(1)
+        }
+    };
+
+    let entries = if path.ends_with(".bin") {
+        read_binary(file)
+    } else {
+        read_text(file)
+    };
+
+    match entries {
+        Ok(entries) => entries,
+        Err(error) => {
+            eprintln!("could not read {}: {}", path, error);
+            exit(1)
+        }
+    }
+}
+
+fn main() {
+    let mut epochs = 10;
+    let mut batch_size = 16384;
+    let mut lr = 0.001;
+    let mut wdl = 0.5;
+    let mut optimizer = Optimizer::Adam;
+    let mut output = None;
+    let mut data_paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--epochs" => epochs = parse_value(&arg, args.next()),
+            "--batch-size" => batch_size = parse_value::<usize>(&arg, args.next()).max(1),
+            "--lr" => lr = parse_value(&arg, args.next()),
+            "--wdl" => wdl = parse_value::<f32>(&arg, args.next()).clamp(0.0, 1.0),
+            "--optimizer" => match args.next().as_deref() {
+                Some("adam") => optimizer = Optimizer::Adam,
+                Some("sgd") => optimizer = Optimizer::Sgd,
+                _ => {
+                    eprintln!("--optimizer expects adam or sgd\n{}", USAGE);
+                    exit(1)
+                }
+            },
+            "-o" | "--output" => output = args.next(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => data_paths.push(arg),
+        }
+    }
+
+    let Some(output) = output else {
+        eprintln!("no output file given\n{}", USAGE);
+        exit(1)
+    };
+
+    // .bin files are read as the binary datagen format, anything else as text
+    let mut entries: Vec<TrainingEntry> = data_paths
+        .iter()
+        .flat_map(|path| load_entries(path))
+        .collect();
+
+    if entries.is_empty() {
+        eprintln!("no training data given\n{}", USAGE);
+        exit(1)
+    }
+    println!("{} positions loaded", entries.len());
+
+    let mut trainer = Trainer::new();
+
+    for epoch in 1..=epochs {
+        // fisher yates so batches mix positions from different games
+        for index in (1..entries.len()).rev() {
+            entries.swap(index, gen_range(0, index + 1));
+        }
+
+        let mut total_loss = 0.0;
+        for batch in entries.chunks(batch_size) {
+            for entry in batch {
+                total_loss += trainer.train_entry(entry, wdl);
+            }
+            trainer.step(optimizer, lr, batch.len());
+        }
+
+        println!(
+            "epoch {}: loss {:.6}",
+            epoch,
+            total_loss / entries.len() as f32
+        );
+
+        if let Err(error) = std::fs::write(&output, trainer.quantise().to_bytes()) {
+            eprintln!("could not write {}: {}", output, error);
+            exit(1)
+        }
+    }
+
+    println!("network written to {}", output);
+}
@@ -0,0 +1,233 @@
+use std::{fs, process::exit, thread};
+
+use chess_engine::{
+    eval::{eval_with_params, params::EvalParams},
+    player::Color,
+    position::Position,
+    training_data::{result_value, TrainingEntry},
+};
+
+const USAGE: &str =
+    "usage: tune [--iterations N] [--threads N] [--step N] [--k F] [-o params.rs] positions.txt";
+
+// a quiet position and how the game it came from ended, 1 white won, 0.5 drawn, 0 black won
+struct Sample {
+    position: Position,
+    result: f64,
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("{} expects a number\n{}", flag, USAGE);
+            exit(1)
+        }
+    }
+}
+
+// a fen followed by the result, either as a pgn result anywhere after it ("1-0", [0-1], c9 "1/2-1/2";)
+// or as a number on its own at the end of the line, or a datagen text line
+fn parse_sample(line: &str) -> Option<Sample> {
+    if let Ok(entry) = TrainingEntry::from_text_line(line) {
+        return Some(Sample {
+            position: entry.position,
+            result: result_value(entry.result) as f64,
+        });
+    }
+
+    let pgn_result = [("1/2-1/2", 0.5), ("1-0", 1.0), ("0-1", 0.0)]
+        .into_iter()
+        .find_map(|(text, result)| line.find(text).map(|start| (&line[..start], result)));
+
+    let (fen, result) = match pgn_result {
+        Some(fen_and_result) => fen_and_result,
+        None => {
+            let (fen, result) = line.trim().rsplit_once(char::is_whitespace)?;
+            (
+                fen,
+                result.trim_matches(|c| c == '[' || c == ']').parse().ok()?,
+            )
+        }
+    };
+
+    let mut fields: Vec<&str> = fen
+        .split_whitespace()
+        .map(|field| field.trim_matches(|c| matches!(c, '[' | ']' | '"' | ';')))
+        .filter(|field| !field.is_empty() && *field != "c9")
+        .collect();
+
+    // epd only has the first four fields
+    if fields.len() == 4 {
+        fields.extend(["0", "1"]);
+    }
+
+    let position = Position::from_fen(&fields.join(" ")).ok()?;
+    Some(Sample { position, result })
+}
+
+fn sigmoid(score: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * score / 400.0))
+}
+
+// mean squared difference between the results and what the evaluation predicts
+fn error(samples: &[Sample], params: &EvalParams, k: f64, threads: usize) -> f64 {
+    let chunk_size = samples.len().div_ceil(threads).max(1);
+
+    let total: f64 = thread::scope(|scope| {
+        let handles: Vec<_> = samples
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|sample| {
+                            let score = eval_with_params(&sample.position, params);
+                            let white_score = match sample.position.side_to_move {
+                                Color::White => score,
+                                Color::Black => -score,
+                            };
+
+                            (sample.result - sigmoid(white_score as f64, k)).powi(2)
+                        })
+                        .sum::<f64>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    });
+
+    total / samples.len() as f64
+}
+
+// scan for the scaling constant that fits the current weights best, narrowing each pass
+fn fit_k(samples: &[Sample], params: &EvalParams, threads: usize) -> f64 {
+    let (mut start, mut end, mut step) = (0.0, 3.0, 0.1);
+    let mut best_k = 1.0;
+
+    for _ in 0..4 {
+        let mut best_error = f64::MAX;
+        let mut k = start;
+
+        while k <= end {
+            let k_error = error(samples, params, k, threads);
+            if k_error < best_error {
+                best_error = k_error;
+                best_k = k;
+            }
+            k += step;
+        }
+
+        start = (best_k - step).max(0.0);
+        end = best_k + step;
+        step /= 10.0;
+    }
+
+    best_k
+}
+
+fn write_params(path: &str, params: &EvalParams) {
+    if let Err(error) = fs::write(path, params.to_rust_source()) {
+        eprintln!("could not write {}: {}", path, error);
+        exit(1)
+    }
+}
+
+fn main() {
+    let mut iterations = 100;
+    let mut threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut step = 1;
+    let mut k = None;
+    let mut output = "tuned_params.rs".to_owned();
+    let mut positions_path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => iterations = parse_value(&arg, args.next()),
+            "--threads" => threads = parse_value::<usize>(&arg, args.next()).max(1),
+            "--step" => step = parse_value(&arg, args.next()),
+            "--k" => k = Some(parse_value(&arg, args.next())),
+            "-o" | "--output" => output = args.next().unwrap_or(output),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => positions_path = Some(arg),
+        }
+    }
+
+    let Some(positions_path) = positions_path else {
+        eprintln!("no positions file given\n{}", USAGE);
+        exit(1)
+    };
+
+    let text = match fs::read_to_string(&positions_path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("could not read {}: {}", positions_path, error);
+            exit(1)
+        }
+    };
+
+    // positions in check are not quiet so their static evaluation means little
+    let samples: Vec<Sample> = text
+        .lines()
+        .filter_map(parse_sample)
+        .filter(|sample| !sample.position.in_check())
+        .collect();
+
+    if samples.is_empty() {
+        eprintln!("no usable positions in {}", positions_path);
+        exit(1)
+    }
+    println!("{} positions loaded", samples.len());
+
+    let mut params = EvalParams::default();
+
+    let k = k.unwrap_or_else(|| fit_k(&samples, &params, threads));
+    println!("k = {:.4}", k);
+
+    let weight_count = params.weight_count();
+    let mut best_error = error(&samples, &params, k, threads);
+    println!("starting error {:.6}", best_error);
+
+    // nudge each weight up then down and keep whichever lowers the error, until nothing does
+    for iteration in 1..=iterations {
+        let mut improved = 0;
+
+        for index in 0..weight_count {
+            for delta in [step, -2 * step] {
+                *params.weight_mut(index) += delta;
+
+                let new_error = error(&samples, &params, k, threads);
+                if new_error < best_error {
+                    best_error = new_error;
+                    improved += 1;
+                    break;
+                }
+
+                // put it back once both directions have been tried
+                if delta < 0 {
+                    *params.weight_mut(index) += step;
+                }
+            }
+        }
+
+        println!(
+            "iteration {}: error {:.6}, {} weights changed",
+            iteration, best_error, improved
+        );
+        write_params(&output, &params);
+
+        if improved == 0 {
+            break;
+        }
+    }
+
+    println!("tuned params written to {}", output);
+}
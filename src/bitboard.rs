@@ -1,4 +1,4 @@
-use crate::player::Color;
+use std::sync::OnceLock;
 
 pub const FILE_A: u64 = 0x0101010101010101;
 pub const FILE_B: u64 = FILE_A << 1;
@@ -18,6 +18,13 @@ pub const RANK_6: u64 = RANK_1 << (8 * 5);
 pub const RANK_7: u64 = RANK_1 << (8 * 6);
 pub const RANK_8: u64 = RANK_1 << (8 * 7);
 
+pub const FILES: [u64; 8] = [
+    FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H,
+];
+pub const RANKS: [u64; 8] = [
+    RANK_1, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8,
+];
+
 #[derive(Clone, Copy)]
 pub enum Direction {
     Up,
@@ -108,6 +115,87 @@ impl Bitboard for u64 {
     }
 }
 
+// every mask table generated once and shared, so callers dont have to thread a dozen arrays around
+pub struct MoveMasks {
+    pub pawn_moves: [[u64; 64]; 2],
+    pub pawn_attacks: [[u64; 64]; 2],
+    pub knight_moves: [u64; 64],
+    pub bishop_moves: [u64; 64],
+    pub rook_moves: [u64; 64],
+    pub queen_moves: [u64; 64],
+    pub king_moves: [u64; 64],
+}
+
+impl MoveMasks {
+    pub fn new() -> Self {
+        MoveMasks {
+            pawn_moves: generate_move_masks_pawn(),
+            pawn_attacks: generate_attack_masks_pawn(),
+            knight_moves: generate_move_masks_knight(),
+            bishop_moves: generate_move_masks_bishop(),
+            rook_moves: generate_move_masks_rook(),
+            queen_moves: generate_move_masks_queen(),
+            king_moves: generate_move_masks_king(),
+        }
+    }
+}
+
+impl Default for MoveMasks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn move_masks() -> &'static MoveMasks {
+    static MOVE_MASKS: OnceLock<MoveMasks> = OnceLock::new();
+    MOVE_MASKS.get_or_init(MoveMasks::new)
+}
+
+// squares a bishop on board_index sees, stopping at (and including) the first piece of either color
+pub fn bishop_attacks(occupied_bitboard: u64, board_index: usize) -> u64 {
+    possible_moves_bishop(
+        occupied_bitboard,
+        occupied_bitboard,
+        &move_masks().bishop_moves,
+        board_index,
+    )
+}
+
+pub fn rook_attacks(occupied_bitboard: u64, board_index: usize) -> u64 {
+    possible_moves_rook(
+        occupied_bitboard,
+        occupied_bitboard,
+        &move_masks().rook_moves,
+        board_index,
+    )
+}
+
+pub fn queen_attacks(occupied_bitboard: u64, board_index: usize) -> u64 {
+    bishop_attacks(occupied_bitboard, board_index) | rook_attacks(occupied_bitboard, board_index)
+}
+
+// smear every bit up the board to the top rank
+pub fn north_fill(mut bitboard: u64) -> u64 {
+    bitboard |= bitboard << 8;
+    bitboard |= bitboard << 16;
+    bitboard |= bitboard << 32;
+
+    bitboard
+}
+
+pub fn south_fill(mut bitboard: u64) -> u64 {
+    bitboard |= bitboard >> 8;
+    bitboard |= bitboard >> 16;
+    bitboard |= bitboard >> 32;
+
+    bitboard
+}
+
+// every file with at least one bit set, filled top to bottom
+pub fn file_fill(bitboard: u64) -> u64 {
+    north_fill(bitboard) | south_fill(bitboard)
+}
+
 pub fn print_bitboard(bitboard: u64) {
     let bitboard = bitboard.flip_over_vertical();
     for i in 0..8 {
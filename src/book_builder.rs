@@ -0,0 +1,150 @@
+use std::{io::Write, path::Path};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    pgn::{GameResult, PgnGame},
+    player::Color,
+    polyglot::{encode_polyglot_move, polyglot_key, PolyglotEntry},
+    position::Position,
+};
+
+// results counted from the point of view of the side that played the move
+#[derive(Default, Clone, Copy, Debug)]
+pub struct MoveStats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MoveStats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn score(&self) -> f32 {
+        (self.wins as f32 + self.draws as f32 / 2.0) / self.games().max(1) as f32
+    }
+
+    // the usual polyglot weighting, a win counts double a draw
+    pub fn weight(&self) -> u32 {
+        2 * self.wins + self.draws
+    }
+}
+
+pub struct BookBuilder {
+    pub max_ply: usize,
+    pub min_games: u32,
+    pub min_score: f32,
+    // keyed by polyglot key and polyglot move
+    pub stats: FxHashMap<(u64, u16), MoveStats>,
+}
+
+impl BookBuilder {
+    pub fn new(max_ply: usize, min_games: u32, min_score: f32) -> Self {
+        BookBuilder {
+            max_ply,
+            min_games,
+            min_score,
+            stats: FxHashMap::default(),
+        }
+    }
+
+    // returns false if the game was skipped, either no result or a move that doesnt parse
+    pub fn add_game(&mut self, game: &PgnGame) -> bool {
+        if game.result == GameResult::Unknown {
+            return false;
+        }
+
+        let mut position = match game.tag("FEN") {
+            Some(fen) => match Position::from_fen(fen) {
+                Ok(position) => position,
+                Err(_) => return false,
+            },
+            None => Position::new(),
+        };
+
+        // every move has to parse before any of them counts, a broken game adds nothing
+        let mut plies = Vec::new();
+        for san in game.moves.iter().take(self.max_ply) {
+            let Some(played_move) = position.move_from_san(san) else {
+                return false;
+            };
+
+            plies.push((
+                polyglot_key(&position),
+                encode_polyglot_move(played_move),
+                position.side_to_move,
+            ));
+            position.make_move(played_move);
+        }
+
+        for (key, raw_move, side) in plies {
+            let stats = self.stats.entry((key, raw_move)).or_default();
+
+            match (game.result, side) {
+                (GameResult::Draw, _) => stats.draws += 1,
+                (GameResult::WhiteWin, Color::White) | (GameResult::BlackWin, Color::Black) => {
+                    stats.wins += 1
+                }
+                _ => stats.losses += 1,
+            }
+        }
+
+        true
+    }
+
+    pub fn entries(&self) -> Vec<PolyglotEntry> {
+        let mut entries: Vec<(PolyglotEntry, u32)> = self
+            .stats
+            .iter()
+            .filter(|(_, stats)| stats.games() >= self.min_games && stats.score() >= self.min_score)
+            .map(|(&(key, raw_move), stats)| {
+                let entry = PolyglotEntry {
+                    key,
+                    raw_move,
+                    weight: 0,
+                    learn: 0,
+                };
+                (entry, stats.weight())
+            })
+            .collect();
+
+        // within a position highest weight first, the order most readers expect
+        entries.sort_by(|(a, a_weight), (b, b_weight)| {
+            a.key
+                .cmp(&b.key)
+                .then(b_weight.cmp(a_weight))
+                .then(a.raw_move.cmp(&b.raw_move))
+        });
+
+        let mut result = Vec::with_capacity(entries.len());
+        for position_entries in entries.chunk_by(|(a, _), (b, _)| a.key == b.key) {
+            // scale down so the biggest weight fits in a u16, keeping every kept move playable
+            let max_weight = position_entries[0].1.max(1);
+            let scale = (u16::MAX as f64 / max_weight as f64).min(1.0);
+
+            for (entry, weight) in position_entries {
+                result.push(PolyglotEntry {
+                    weight: ((*weight as f64 * scale) as u16).max(1),
+                    ..*entry
+                });
+            }
+        }
+
+        result
+    }
+
+    // returns how many entries made it through the filters
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<usize> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let entries = self.entries();
+
+        for entry in &entries {
+            file.write_all(&entry.to_bytes())?;
+        }
+        file.flush()?;
+
+        Ok(entries.len())
+    }
+}
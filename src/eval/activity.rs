@@ -0,0 +1,105 @@
+use crate::{
+    bitboard::{south_fill, Bitboard, Direction, FILES, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8},
+    eval::{params::EvalParams, piece_attacks, Score},
+    player::{Color, PieceType},
+    position::Position,
+};
+
+// a typical number of safe squares for each piece type, mobility is counted from here
+pub const MOBILITY_BASELINE: [i32; 6] = [0, 4, 6, 7, 13, 0];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ActivityTerms {
+    pub mobility: Score,
+    pub outposts: Score,
+    pub rook_files: Score,
+    pub seventh_rank: Score,
+    pub bishop_pair: Score,
+}
+
+impl ActivityTerms {
+    pub fn total(&self) -> Score {
+        self.mobility + self.outposts + self.rook_files + self.seventh_rank + self.bishop_pair
+    }
+}
+
+// how active colors pieces are, worked out as if color were white
+pub fn evaluate_activity(position: &Position, color: Color, params: &EvalParams) -> ActivityTerms {
+    let relative = |bitboard: u64| match color {
+        Color::White => bitboard,
+        Color::Black => bitboard.flip_over_horizontal(),
+    };
+
+    let own = position.player(color);
+    let enemy = position.player(color.opposite_color());
+
+    let own_pieces = relative(own.all_bitboards());
+    let own_pawns = relative(own.pawn_bitboard);
+    let enemy_pawns = relative(enemy.pawn_bitboard);
+    let occupied_bitboard = relative(position.occupied_bitboard());
+
+    let own_pawn_attacks = own_pawns.shift(Direction::UpLeft) | own_pawns.shift(Direction::UpRight);
+    let enemy_pawn_attacks =
+        enemy_pawns.shift(Direction::DownLeft) | enemy_pawns.shift(Direction::DownRight);
+
+    // a square no enemy pawn can ever attack, held by one of ours
+    let outposts = (RANK_4 | RANK_5 | RANK_6) & own_pawn_attacks & !south_fill(enemy_pawn_attacks);
+    let safe_squares = !own_pieces & !enemy_pawn_attacks;
+
+    let mut terms = ActivityTerms::default();
+
+    for piece_type in [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        let mut pieces = relative(own.piece_bitboard(piece_type));
+
+        while pieces != 0 {
+            let board_index = pieces.trailing_zeros() as usize;
+            let square = 1 << board_index;
+
+            let attacks = piece_attacks(piece_type, Color::White, board_index, occupied_bitboard);
+            let safe_count = (attacks & safe_squares).count_ones() as i32;
+            terms.mobility += params.mobility_weights[piece_type as usize]
+                * (safe_count - MOBILITY_BASELINE[piece_type as usize]);
+
+            match piece_type {
+                PieceType::Knight if square & outposts != 0 => {
+                    terms.outposts += params.knight_outpost
+                }
+                PieceType::Bishop if square & outposts != 0 => {
+                    terms.outposts += params.bishop_outpost
+                }
+                PieceType::Rook => {
+                    let file = FILES[board_index % 8];
+
+                    if file & own_pawns == 0 {
+                        terms.rook_files += match file & enemy_pawns {
+                            0 => params.rook_on_open_file,
+                            _ => params.rook_on_semi_open_file,
+                        };
+                    }
+
+                    // only worth it when there are pawns to eat or the king is stuck behind it
+                    let enemy_king = relative(enemy.king_bitboard);
+                    if square & RANK_7 != 0
+                        && (enemy_pawns & RANK_7 != 0 || enemy_king & RANK_8 != 0)
+                    {
+                        terms.seventh_rank += params.rook_on_seventh_rank;
+                    }
+                }
+                _ => {}
+            }
+
+            pieces &= pieces - 1;
+        }
+    }
+
+    if own.bishop_bitboard.count_ones() >= 2 {
+        terms.bishop_pair = params.bishop_pair;
+    }
+
+    terms
+}
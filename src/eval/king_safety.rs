@@ -0,0 +1,159 @@
+use crate::{
+    bitboard::{bishop_attacks, move_masks, rook_attacks, Bitboard, Direction, FILES},
+    eval::{attack_map, params::EvalParams, piece_attacks, s, Score},
+    player::{Color, PieceType},
+    position::Position,
+};
+
+// danger grows quadratically so a pile of attackers is far worse than the sum of each alone
+pub const KING_DANGER_DIVISOR: i32 = 4;
+pub const MAX_KING_DANGER: i32 = 800;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KingSafetyTerms {
+    pub attacks: Score,
+    pub safe_checks: Score,
+    pub shield: Score,
+    pub storm: Score,
+    pub open_files: Score,
+}
+
+impl KingSafetyTerms {
+    pub fn total(&self) -> Score {
+        self.attacks + self.safe_checks + self.shield + self.storm + self.open_files
+    }
+}
+
+// distance in ranks from the king to the closest pawn in front of it, 0 if there is none
+fn pawn_distance(pawns_in_front: u64, king_rank: usize) -> usize {
+    match pawns_in_front {
+        0 => 0,
+        pawns => pawns.trailing_zeros() as usize / 8 - king_rank,
+    }
+}
+
+// shield, storm and open files, looked at as if color were white
+fn evaluate_king_pawns(
+    own_pawns: u64,
+    enemy_pawns: u64,
+    king_square: usize,
+    params: &EvalParams,
+) -> [Score; 3] {
+    let (king_file, king_rank) = (king_square % 8, king_square / 8);
+    let in_front = u64::MAX
+        .checked_shl(8 * (king_rank as u32 + 1))
+        .unwrap_or(0);
+
+    let mut shield = Score::ZERO;
+    let mut storm = Score::ZERO;
+    let mut open_files = Score::ZERO;
+
+    for &file in &FILES[king_file.saturating_sub(1)..=(king_file + 1).min(7)] {
+        shield += params.pawn_shield[pawn_distance(own_pawns & file & in_front, king_rank)];
+        storm += params.pawn_storm[pawn_distance(enemy_pawns & file & in_front, king_rank)];
+
+        if own_pawns & file == 0 {
+            open_files += match enemy_pawns & file {
+                0 => params.open_file_near_king,
+                _ => params.semi_open_file_near_king,
+            };
+        }
+    }
+
+    [shield, storm, open_files]
+}
+
+// how exposed colors king is, negative is bad for color
+pub fn evaluate_king_safety(
+    position: &Position,
+    color: Color,
+    params: &EvalParams,
+) -> KingSafetyTerms {
+    let king_square = position.king_square(color);
+    let enemy_color = color.opposite_color();
+    let own = position.player(color);
+    let enemy = position.player(enemy_color);
+
+    let [shield, storm, open_files] = match color {
+        Color::White => {
+            evaluate_king_pawns(own.pawn_bitboard, enemy.pawn_bitboard, king_square, params)
+        }
+        Color::Black => evaluate_king_pawns(
+            own.pawn_bitboard.flip_over_horizontal(),
+            enemy.pawn_bitboard.flip_over_horizontal(),
+            king_square ^ 56,
+            params,
+        ),
+    };
+
+    // the squares around the king plus a row further towards the enemy
+    let masks = move_masks();
+    let king_moves = masks.king_moves[king_square];
+    let king_zone = (1 << king_square)
+        | king_moves
+        | match color {
+            Color::White => king_moves.shift(Direction::Up),
+            Color::Black => king_moves.shift(Direction::Down),
+        };
+
+    let occupied_bitboard = position.occupied_bitboard();
+    let safe = !attack_map(position, color) & !enemy.all_bitboards();
+    let bishop_checks = bishop_attacks(occupied_bitboard, king_square);
+    let rook_checks = rook_attacks(occupied_bitboard, king_square);
+    let check_squares = [
+        0,
+        masks.knight_moves[king_square],
+        bishop_checks,
+        rook_checks,
+        bishop_checks | rook_checks,
+        0,
+    ];
+
+    let mut attackers = 0;
+    let mut danger = 0;
+    let mut safe_checks = Score::ZERO;
+
+    for piece_type in [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        let mut pieces = enemy.piece_bitboard(piece_type);
+        let mut reachable = 0;
+
+        while pieces != 0 {
+            let board_index = pieces.trailing_zeros() as usize;
+            let attacks = piece_attacks(piece_type, enemy_color, board_index, occupied_bitboard);
+
+            if attacks & king_zone != 0 {
+                attackers += 1;
+                danger += params.king_attack_weights[piece_type as usize]
+                    * (attacks & king_zone).count_ones() as i32;
+            }
+
+            reachable |= attacks;
+            pieces &= pieces - 1;
+        }
+
+        if reachable & check_squares[piece_type as usize] & safe != 0 {
+            safe_checks += params.safe_check[piece_type as usize];
+        }
+    }
+
+    // a lone attacker is rarely dangerous on its own
+    let attacks = if attackers >= 2 {
+        let danger = (danger * danger / KING_DANGER_DIVISOR).min(MAX_KING_DANGER);
+        s(-danger, -danger / 8)
+    } else {
+        Score::ZERO
+    };
+
+    KingSafetyTerms {
+        attacks,
+        safe_checks,
+        shield,
+        storm,
+        open_files,
+    }
+}
@@ -0,0 +1,351 @@
+pub mod activity;
+pub mod king_safety;
+pub mod params;
+pub mod pawns;
+pub mod trace;
+
+use std::{
+    cell::RefCell,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use crate::{
+    bitboard::{bishop_attacks, move_masks, queen_attacks, rook_attacks, Bitboard},
+    eval::{
+        activity::evaluate_activity,
+        king_safety::evaluate_king_safety,
+        params::{EvalParams, DEFAULT_PARAMS},
+        pawns::{evaluate_pawns, PawnEntry, PawnHashTable},
+    },
+    player::{Color, PieceType, Player},
+    position::Position,
+};
+
+thread_local! {
+    // one per thread so search threads never contend for it
+    static PAWN_HASH_TABLE: RefCell<PawnHashTable> = RefCell::new(PawnHashTable::default());
+}
+
+// every term has a middlegame and an endgame value, blended by how much material is left
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+pub const fn s(mg: i32, eg: i32) -> Score {
+    Score { mg, eg }
+}
+
+impl Score {
+    pub const ZERO: Score = s(0, 0);
+
+    // phase runs from MAX_PHASE with all pieces on the board down to 0 with only kings and pawns
+    pub fn taper(&self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        s(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        s(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        s(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, factor: i32) -> Score {
+        s(self.mg * factor, self.eg * factor)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        *self = *self - other;
+    }
+}
+
+// how much each piece type counts towards the game phase, pawns and kings dont
+pub const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24;
+
+// the hand written starting point for the tables in DEFAULT_PARAMS
+// piece square tables written as seen from white, rank 8 on the top row
+// so the visual index for a white piece is board_index ^ 56
+#[rustfmt::skip]
+pub const PAWN_TABLE_MG: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+pub const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+pub const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+pub const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+pub const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+pub const KING_TABLE_MG: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+// pawns are worth more the closer they get to promoting once the board empties
+#[rustfmt::skip]
+pub const PAWN_TABLE_EG: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    80, 80, 80, 80, 80, 80, 80, 80,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    30, 30, 30, 30, 30, 30, 30, 30,
+    15, 15, 15, 15, 15, 15, 15, 15,
+     5,  5,  5,  5,  5,  5,  5,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+// without queens around the king wants to be in the middle of things
+#[rustfmt::skip]
+pub const KING_TABLE_EG: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+pub const PIECE_SQUARE_TABLES_MG: [[i32; 64]; 6] = [
+    PAWN_TABLE_MG,
+    KNIGHT_TABLE,
+    BISHOP_TABLE,
+    ROOK_TABLE,
+    QUEEN_TABLE,
+    KING_TABLE_MG,
+];
+
+pub const PIECE_SQUARE_TABLES_EG: [[i32; 64]; 6] = [
+    PAWN_TABLE_EG,
+    KNIGHT_TABLE,
+    BISHOP_TABLE,
+    ROOK_TABLE,
+    QUEEN_TABLE,
+    KING_TABLE_EG,
+];
+
+// 0 once only kings and pawns are left, MAX_PHASE at the start (more with promoted pieces so clamp)
+pub fn game_phase(position: &Position) -> i32 {
+    let phase: i32 = [&position.white_player, &position.black_player]
+        .into_iter()
+        .flat_map(|player| {
+            PieceType::ALL.into_iter().map(move |piece_type| {
+                player.piece_bitboard(piece_type).count_ones() as i32
+                    * PHASE_WEIGHTS[piece_type as usize]
+            })
+        })
+        .sum();
+
+    phase.min(MAX_PHASE)
+}
+
+// squares a piece of color standing on board_index attacks
+pub fn piece_attacks(
+    piece_type: PieceType,
+    color: Color,
+    board_index: usize,
+    occupied_bitboard: u64,
+) -> u64 {
+    let masks = move_masks();
+
+    match piece_type {
+        PieceType::Pawn => masks.pawn_attacks[color as usize][board_index],
+        PieceType::Knight => masks.knight_moves[board_index],
+        PieceType::Bishop => bishop_attacks(occupied_bitboard, board_index),
+        PieceType::Rook => rook_attacks(occupied_bitboard, board_index),
+        PieceType::Queen => queen_attacks(occupied_bitboard, board_index),
+        PieceType::King => masks.king_moves[board_index],
+    }
+}
+
+// every square attacked by at least one piece of color
+pub fn attack_map(position: &Position, color: Color) -> u64 {
+    let occupied_bitboard = position.occupied_bitboard();
+    let player = position.player(color);
+    let mut attacks = 0;
+
+    for piece_type in PieceType::ALL {
+        let mut pieces = player.piece_bitboard(piece_type);
+
+        while pieces != 0 {
+            let board_index = pieces.trailing_zeros() as usize;
+            attacks |= piece_attacks(piece_type, color, board_index, occupied_bitboard);
+            pieces &= pieces - 1;
+        }
+    }
+
+    attacks
+}
+
+// material for one side, the king is never traded so its value should stay 0
+fn material(player: &Player, params: &EvalParams) -> Score {
+    PieceType::ALL
+        .into_iter()
+        .fold(Score::ZERO, |score, piece_type| {
+            score
+                + params.piece_values[piece_type as usize]
+                    * player.piece_bitboard(piece_type).count_ones() as i32
+        })
+}
+
+// piece square score for one side, from that sides point of view
+fn piece_squares(player: &Player, params: &EvalParams) -> Score {
+    let mut score = Score::ZERO;
+
+    for piece_type in PieceType::ALL {
+        // mirror black onto white's side of the board so both share the same tables
+        let mut bitboard = match player.color {
+            Color::White => player.piece_bitboard(piece_type),
+            Color::Black => player.piece_bitboard(piece_type).flip_over_horizontal(),
+        };
+
+        let table_mg = &params.piece_square_mg[piece_type as usize];
+        let table_eg = &params.piece_square_eg[piece_type as usize];
+
+        while bitboard != 0 {
+            let table_index = bitboard.trailing_zeros() as usize ^ 56;
+            score += s(table_mg[table_index], table_eg[table_index]);
+            bitboard &= bitboard - 1;
+        }
+    }
+
+    score
+}
+
+fn evaluate_player(player: &Player, params: &EvalParams) -> Score {
+    material(player, params) + piece_squares(player, params)
+}
+
+// white minus black before tapering
+fn evaluate_score(position: &Position, params: &EvalParams, pawn_entry: &PawnEntry) -> Score {
+    let white = Color::White;
+    let black = Color::Black;
+
+    let mut score = evaluate_player(&position.white_player, params)
+        - evaluate_player(&position.black_player, params);
+    score += pawn_entry.score(white) - pawn_entry.score(black);
+    score += evaluate_king_safety(position, white, params).total()
+        - evaluate_king_safety(position, black, params).total();
+    score += evaluate_activity(position, white, params).total()
+        - evaluate_activity(position, black, params).total();
+
+    score
+}
+
+fn from_side_to_move(position: &Position, score: i32) -> i32 {
+    match position.side_to_move {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+// static evaluation in centipawns from the point of view of the side to move
+pub fn eval(position: &Position) -> i32 {
+    let pawn_entry =
+        PAWN_HASH_TABLE.with(|table| table.borrow_mut().probe(position, &DEFAULT_PARAMS));
+    let score = evaluate_score(position, &DEFAULT_PARAMS, &pawn_entry).taper(game_phase(position));
+
+    from_side_to_move(position, score)
+}
+
+// what the engine searches with, the network when nnue is turned on for this position, else the handcrafted eval
+pub fn evaluate(position: &Position) -> i32 {
+    position.nnue_eval().unwrap_or_else(|| eval(position))
+}
+
+// the same as eval with other weights, skips the pawn hash table since its entries are for the defaults
+pub fn eval_with_params(position: &Position, params: &EvalParams) -> i32 {
+    let pawn_entry = evaluate_pawns(position, params);
+    let score = evaluate_score(position, params, &pawn_entry).taper(game_phase(position));
+
+    from_side_to_move(position, score)
+}
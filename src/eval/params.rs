@@ -0,0 +1,308 @@
+use std::{fmt::Write, slice};
+
+use crate::eval::{s, Score, PIECE_SQUARE_TABLES_EG, PIECE_SQUARE_TABLES_MG};
+
+// every weight the evaluation uses, kept together so the tuner can change them all at once
+// arrays indexed by piece type go pawn, knight, bishop, rook, queen, king
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalParams {
+    pub piece_values: [Score; 6],
+    // written as seen from white with rank 8 first, see the tables in eval
+    pub piece_square_mg: [[i32; 64]; 6],
+    pub piece_square_eg: [[i32; 64]; 6],
+
+    pub doubled_pawn: Score,
+    pub isolated_pawn: Score,
+    pub backward_pawn: Score,
+    // indexed by rank from the pawns own side, rank 1 and 8 never have pawns
+    pub connected_pawn: [Score; 8],
+    pub passed_pawn: [Score; 8],
+    pub candidate_passed_pawn: [Score; 8],
+
+    // danger added for every square of the king zone an attacker of this type hits
+    pub king_attack_weights: [i32; 6],
+    // once per piece type that can give a check on a square the king side doesnt cover
+    pub safe_check: [Score; 6],
+    // indexed by how many ranks in front of the king the closest pawn on the file is, 0 means none
+    pub pawn_shield: [Score; 8],
+    pub pawn_storm: [Score; 8],
+    pub open_file_near_king: Score,
+    pub semi_open_file_near_king: Score,
+
+    // per safe square, counted from a typical number of squares so an average piece scores about 0
+    pub mobility_weights: [Score; 6],
+    pub knight_outpost: Score,
+    pub bishop_outpost: Score,
+    pub rook_on_open_file: Score,
+    pub rook_on_semi_open_file: Score,
+    pub rook_on_seventh_rank: Score,
+    pub bishop_pair: Score,
+}
+
+enum WeightGroup<'a> {
+    Values(&'a mut [i32]),
+    Scores(&'a mut [Score]),
+}
+
+impl<'a> WeightGroup<'a> {
+    fn len(&self) -> usize {
+        match self {
+            WeightGroup::Values(values) => values.len(),
+            WeightGroup::Scores(scores) => 2 * scores.len(),
+        }
+    }
+
+    fn get_mut(self, index: usize) -> &'a mut i32 {
+        match self {
+            WeightGroup::Values(values) => &mut values[index],
+            WeightGroup::Scores(scores) => scores_mut(scores).nth(index).unwrap(),
+        }
+    }
+}
+
+fn scores_mut(scores: &mut [Score]) -> impl Iterator<Item = &mut i32> {
+    scores.iter_mut().flat_map(|Score { mg, eg }| [mg, eg])
+}
+
+fn write_score(source: &mut String, score: Score) {
+    write!(source, "s({}, {})", score.mg, score.eg).unwrap();
+}
+
+fn write_scores(source: &mut String, name: &str, scores: &[Score]) {
+    write!(source, "    {}: [", name).unwrap();
+    for (index, score) in scores.iter().enumerate() {
+        if index > 0 {
+            source.push_str(", ");
+        }
+        write_score(source, *score);
+    }
+    source.push_str("],\n");
+}
+
+// one row per rank like the hand written tables
+fn write_tables(source: &mut String, name: &str, tables: &[[i32; 64]; 6]) {
+    writeln!(source, "    {}: [", name).unwrap();
+    for table in tables {
+        source.push_str("        [\n");
+        for row in table.chunks(8) {
+            let row: Vec<String> = row.iter().map(|value| format!("{:4},", value)).collect();
+            writeln!(source, "           {}", row.concat()).unwrap();
+        }
+        source.push_str("        ],\n");
+    }
+    source.push_str("    ],\n");
+}
+
+impl EvalParams {
+    // every tunable number in a fixed order, split into runs of values and scores
+    fn weight_groups(&mut self) -> [WeightGroup<'_>; 22] {
+        use WeightGroup::{Scores, Values};
+
+        [
+            Scores(&mut self.piece_values),
+            Values(self.piece_square_mg.as_flattened_mut()),
+            Values(self.piece_square_eg.as_flattened_mut()),
+            Scores(slice::from_mut(&mut self.doubled_pawn)),
+            Scores(slice::from_mut(&mut self.isolated_pawn)),
+            Scores(slice::from_mut(&mut self.backward_pawn)),
+            Scores(&mut self.connected_pawn),
+            Scores(&mut self.passed_pawn),
+            Scores(&mut self.candidate_passed_pawn),
+            Values(&mut self.king_attack_weights),
+            Scores(&mut self.safe_check),
+            Scores(&mut self.pawn_shield),
+            Scores(&mut self.pawn_storm),
+            Scores(slice::from_mut(&mut self.open_file_near_king)),
+            Scores(slice::from_mut(&mut self.semi_open_file_near_king)),
+            Scores(&mut self.mobility_weights),
+            Scores(slice::from_mut(&mut self.knight_outpost)),
+            Scores(slice::from_mut(&mut self.bishop_outpost)),
+            Scores(slice::from_mut(&mut self.rook_on_open_file)),
+            Scores(slice::from_mut(&mut self.rook_on_semi_open_file)),
+            Scores(slice::from_mut(&mut self.rook_on_seventh_rank)),
+            Scores(slice::from_mut(&mut self.bishop_pair)),
+        ]
+    }
+
+    // every tunable number in a fixed order, middlegame before endgame for each score
+    pub fn weights_mut(&mut self) -> Vec<&mut i32> {
+        let mut weights: Vec<&mut i32> = Vec::new();
+
+        for group in self.weight_groups() {
+            match group {
+                WeightGroup::Values(values) => weights.extend(values.iter_mut()),
+                WeightGroup::Scores(scores) => weights.extend(scores_mut(scores)),
+            }
+        }
+
+        weights
+    }
+
+    pub fn weight_count(&mut self) -> usize {
+        self.weight_groups().iter().map(WeightGroup::len).sum()
+    }
+
+    // the same weight as weights_mut()[index] without building the whole list
+    pub fn weight_mut(&mut self, mut index: usize) -> &mut i32 {
+        for group in self.weight_groups() {
+            if index < group.len() {
+                return group.get_mut(index);
+            }
+            index -= group.len();
+        }
+
+        panic!("weight index out of range");
+    }
+
+    // the params as a DEFAULT_PARAMS declaration that can be pasted over the one below
+    pub fn to_rust_source(&self) -> String {
+        let mut source = String::new();
+
+        source.push_str("#[rustfmt::skip]\n");
+        source.push_str("pub const DEFAULT_PARAMS: EvalParams = EvalParams {\n");
+
+        write_scores(&mut source, "piece_values", &self.piece_values);
+        write_tables(&mut source, "piece_square_mg", &self.piece_square_mg);
+        write_tables(&mut source, "piece_square_eg", &self.piece_square_eg);
+
+        for (name, score) in [
+            ("doubled_pawn", self.doubled_pawn),
+            ("isolated_pawn", self.isolated_pawn),
+            ("backward_pawn", self.backward_pawn),
+        ] {
+            write!(source, "    {}: ", name).unwrap();
+            write_score(&mut source, score);
+            source.push_str(",\n");
+        }
+        write_scores(&mut source, "connected_pawn", &self.connected_pawn);
+        write_scores(&mut source, "passed_pawn", &self.passed_pawn);
+        write_scores(
+            &mut source,
+            "candidate_passed_pawn",
+            &self.candidate_passed_pawn,
+        );
+
+        writeln!(
+            source,
+            "    king_attack_weights: {:?},",
+            self.king_attack_weights
+        )
+        .unwrap();
+        write_scores(&mut source, "safe_check", &self.safe_check);
+        write_scores(&mut source, "pawn_shield", &self.pawn_shield);
+        write_scores(&mut source, "pawn_storm", &self.pawn_storm);
+        write_scores(&mut source, "mobility_weights", &self.mobility_weights);
+
+        for (name, score) in [
+            ("open_file_near_king", self.open_file_near_king),
+            ("semi_open_file_near_king", self.semi_open_file_near_king),
+            ("knight_outpost", self.knight_outpost),
+            ("bishop_outpost", self.bishop_outpost),
+            ("rook_on_open_file", self.rook_on_open_file),
+            ("rook_on_semi_open_file", self.rook_on_semi_open_file),
+            ("rook_on_seventh_rank", self.rook_on_seventh_rank),
+            ("bishop_pair", self.bishop_pair),
+        ] {
+            write!(source, "    {}: ", name).unwrap();
+            write_score(&mut source, score);
+            source.push_str(",\n");
+        }
+
+        source.push_str("};\n");
+        source
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_PARAMS
+    }
+}
+
+// the tune binary writes a replacement for this in the same shape
+pub const DEFAULT_PARAMS: EvalParams = EvalParams {
+    piece_values: [
+        s(100, 120),
+        s(320, 290),
+        s(330, 310),
+        s(500, 540),
+        s(900, 950),
+        s(0, 0),
+    ],
+    piece_square_mg: PIECE_SQUARE_TABLES_MG,
+    piece_square_eg: PIECE_SQUARE_TABLES_EG,
+
+    doubled_pawn: s(-10, -25),
+    isolated_pawn: s(-10, -15),
+    backward_pawn: s(-8, -10),
+    connected_pawn: [
+        s(0, 0),
+        s(2, 0),
+        s(5, 2),
+        s(8, 5),
+        s(15, 12),
+        s(25, 25),
+        s(45, 40),
+        s(0, 0),
+    ],
+    passed_pawn: [
+        s(0, 0),
+        s(5, 10),
+        s(5, 15),
+        s(10, 25),
+        s(20, 45),
+        s(40, 80),
+        s(70, 130),
+        s(0, 0),
+    ],
+    candidate_passed_pawn: [
+        s(0, 0),
+        s(2, 5),
+        s(3, 7),
+        s(5, 12),
+        s(10, 22),
+        s(20, 40),
+        s(0, 0),
+        s(0, 0),
+    ],
+
+    king_attack_weights: [0, 2, 2, 3, 5, 0],
+    safe_check: [
+        s(0, 0),
+        s(-30, -10),
+        s(-20, -8),
+        s(-30, -10),
+        s(-25, -10),
+        s(0, 0),
+    ],
+    pawn_shield: [
+        s(-25, -5),
+        s(15, 0),
+        s(8, 0),
+        s(2, 0),
+        s(0, 0),
+        s(0, 0),
+        s(0, 0),
+        s(0, 0),
+    ],
+    pawn_storm: [
+        s(0, 0),
+        s(-5, 0),
+        s(-30, -5),
+        s(-15, 0),
+        s(-5, 0),
+        s(0, 0),
+        s(0, 0),
+        s(0, 0),
+    ],
+    mobility_weights: [s(0, 0), s(4, 4), s(5, 5), s(2, 4), s(1, 2), s(0, 0)],
+
+    open_file_near_king: s(-20, 0),
+    semi_open_file_near_king: s(-10, 0),
+    knight_outpost: s(25, 15),
+    bishop_outpost: s(15, 8),
+    rook_on_open_file: s(25, 10),
+    rook_on_semi_open_file: s(12, 6),
+    rook_on_seventh_rank: s(20, 30),
+    bishop_pair: s(30, 50),
+};
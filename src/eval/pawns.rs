@@ -0,0 +1,187 @@
+use crate::{
+    bitboard::{file_fill, north_fill, south_fill, Bitboard, Direction},
+    eval::{params::EvalParams, Score},
+    player::Color,
+    position::Position,
+};
+
+// each sides pawn structure terms, looked at as if that side were white
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PawnTerms {
+    pub doubled: Score,
+    pub isolated: Score,
+    pub backward: Score,
+    pub connected: Score,
+    pub passed: Score,
+    pub candidate: Score,
+}
+
+impl PawnTerms {
+    pub fn total(&self) -> Score {
+        self.doubled + self.isolated + self.backward + self.connected + self.passed + self.candidate
+    }
+}
+
+// how many of the pawns fill over each square, counted up to two
+// the second pawn on a file is one that the fill from another already passed over
+fn span_counts(pawns: u64, fill: fn(u64) -> u64, step: fn(u64) -> u64) -> [u64; 2] {
+    [fill(pawns), fill(pawns & step(fill(pawns)))]
+}
+
+// adds up two counts from span_counts, giving the squares with at least one to four
+fn add_counts(a: [u64; 2], b: [u64; 2]) -> [u64; 4] {
+    [
+        a[0] | b[0],
+        a[1] | b[1] | (a[0] & b[0]),
+        (a[1] & b[0]) | (a[0] & b[1]),
+        a[1] & b[1],
+    ]
+}
+
+fn rank_bonus(mut pawns: u64, bonus: &[Score; 8]) -> Score {
+    let mut score = Score::ZERO;
+
+    while pawns != 0 {
+        score += bonus[pawns.trailing_zeros() as usize / 8];
+        pawns &= pawns - 1;
+    }
+
+    score
+}
+
+// own pawns move up the board, black passes its pawns in flipped so the same code works for both
+// returns the terms and the passed pawns
+fn evaluate_pawn_structure(own: u64, enemy: u64, params: &EvalParams) -> (PawnTerms, u64) {
+    let count = |bitboard: u64| bitboard.count_ones() as i32;
+
+    let own_files = file_fill(own);
+    let neighbour_files = own_files.shift(Direction::Left) | own_files.shift(Direction::Right);
+    let own_attacks = own.shift(Direction::UpLeft) | own.shift(Direction::UpRight);
+    let enemy_attacks = enemy.shift(Direction::DownLeft) | enemy.shift(Direction::DownRight);
+
+    // squares strictly in front of / behind some pawn, from that pawns point of view
+    let ahead_of_own = north_fill(own) << 8;
+    let behind_own = south_fill(own) >> 8;
+    let ahead_of_enemy = south_fill(enemy) >> 8;
+
+    // only the pawns with another of ours behind them count as doubled
+    let doubled = own & ahead_of_own;
+    let isolated = own & !neighbour_files;
+
+    // no neighbour level or behind that could come up to defend, and stepping forward walks into a pawn
+    let supportable = north_fill(own.shift(Direction::Left) | own.shift(Direction::Right));
+    let stop_attacked = (own << 8 & enemy_attacks) >> 8;
+    let backward = own & !supportable & !isolated & stop_attacked;
+
+    let phalanx = own & (own.shift(Direction::Left) | own.shift(Direction::Right));
+    let connected = (own & own_attacks) | phalanx;
+
+    // nothing in front on this or the neighbouring files can stop it, and it is the front pawn of its file
+    let enemy_spans = ahead_of_enemy
+        | ahead_of_enemy.shift(Direction::Left)
+        | ahead_of_enemy.shift(Direction::Right);
+    let passed = own & !enemy_spans & !behind_own;
+
+    // on a file with no enemy pawn ahead, and as many helpers as there are sentries
+    // helpers are our pawns level or behind on the neighbouring files, filled up the board
+    // sentries are enemy pawns ahead on them, which is where their attack spans reach
+    let up = |bitboard: u64| bitboard << 8;
+    let down = |bitboard: u64| bitboard >> 8;
+    let helpers = add_counts(
+        span_counts(own.shift(Direction::Left), north_fill, up),
+        span_counts(own.shift(Direction::Right), north_fill, up),
+    );
+    let sentries = add_counts(
+        span_counts(enemy.shift(Direction::DownLeft), south_fill, down),
+        span_counts(enemy.shift(Direction::DownRight), south_fill, down),
+    );
+    let outnumbered = (0..4).fold(0, |squares, n| squares | (sentries[n] & !helpers[n]));
+    let candidate = own & !ahead_of_enemy & !passed & !behind_own & !outnumbered;
+
+    let terms = PawnTerms {
+        doubled: params.doubled_pawn * count(doubled),
+        isolated: params.isolated_pawn * count(isolated),
+        backward: params.backward_pawn * count(backward),
+        connected: rank_bonus(connected, &params.connected_pawn),
+        passed: rank_bonus(passed, &params.passed_pawn),
+        candidate: rank_bonus(candidate, &params.candidate_passed_pawn),
+    };
+
+    (terms, passed)
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PawnEntry {
+    pub key: u64,
+    // indexed by color, white then black
+    pub terms: [PawnTerms; 2],
+    pub passed_pawns: [u64; 2],
+}
+
+impl PawnEntry {
+    pub fn score(&self, color: Color) -> Score {
+        self.terms[color as usize].total()
+    }
+}
+
+pub fn evaluate_pawns(position: &Position, params: &EvalParams) -> PawnEntry {
+    let white_pawns = position.white_player.pawn_bitboard;
+    let black_pawns = position.black_player.pawn_bitboard;
+
+    let (white_terms, white_passed) = evaluate_pawn_structure(white_pawns, black_pawns, params);
+    let (black_terms, black_passed) = evaluate_pawn_structure(
+        black_pawns.flip_over_horizontal(),
+        white_pawns.flip_over_horizontal(),
+        params,
+    );
+
+    PawnEntry {
+        key: position.pawn_key,
+        terms: [white_terms, black_terms],
+        passed_pawns: [white_passed, black_passed.flip_over_horizontal()],
+    }
+}
+
+// pawn structure changes rarely during search so the same structures come up again and again
+// entries only hold for the params they were worked out with, clear it before changing them
+pub struct PawnHashTable {
+    entries: Vec<Option<PawnEntry>>,
+}
+
+impl PawnHashTable {
+    // size is rounded down to a power of two so the key can be masked into an index
+    pub fn new(size: usize) -> Self {
+        let size = if size.is_power_of_two() {
+            size
+        } else {
+            (size.next_power_of_two() / 2).max(1)
+        };
+
+        PawnHashTable {
+            entries: vec![None; size],
+        }
+    }
+
+    pub fn probe(&mut self, position: &Position, params: &EvalParams) -> PawnEntry {
+        let index = position.pawn_key as usize & (self.entries.len() - 1);
+
+        match self.entries[index] {
+            Some(entry) if entry.key == position.pawn_key => entry,
+            _ => {
+                let entry = evaluate_pawns(position, params);
+                self.entries[index] = Some(entry);
+                entry
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        Self::new(1 << 14)
+    }
+}
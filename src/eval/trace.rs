@@ -0,0 +1,166 @@
+use std::fmt;
+
+use crate::{
+    eval::{
+        activity::evaluate_activity, game_phase, king_safety::evaluate_king_safety, material,
+        params::DEFAULT_PARAMS, pawns::evaluate_pawns, piece_squares, Score, MAX_PHASE,
+    },
+    player::Color,
+    position::Position,
+};
+
+// one row of the trace, each side from its own point of view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceTerm {
+    pub name: &'static str,
+    pub white: Score,
+    pub black: Score,
+}
+
+impl TraceTerm {
+    // positive is good for white
+    pub fn total(&self) -> Score {
+        self.white - self.black
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalTrace {
+    pub terms: Vec<TraceTerm>,
+    pub phase: i32,
+    pub side_to_move: Color,
+}
+
+impl EvalTrace {
+    pub fn total(&self) -> Score {
+        self.terms
+            .iter()
+            .fold(Score::ZERO, |score, term| score + term.total())
+    }
+
+    // tapered, from whites point of view
+    pub fn white_score(&self) -> i32 {
+        self.total().taper(self.phase)
+    }
+
+    // the same number eval gives
+    pub fn score(&self) -> i32 {
+        match self.side_to_move {
+            Color::White => self.white_score(),
+            Color::Black => -self.white_score(),
+        }
+    }
+}
+
+// every term eval adds up, kept apart so odd evaluations can be picked through
+// the pawn hash table is skipped so this never depends on what was searched before
+pub fn eval_trace(position: &Position) -> EvalTrace {
+    let white = &position.white_player;
+    let black = &position.black_player;
+
+    let params = &DEFAULT_PARAMS;
+
+    let pawns = evaluate_pawns(position, params);
+    let [white_pawns, black_pawns] = pawns.terms;
+    let white_king = evaluate_king_safety(position, Color::White, params);
+    let black_king = evaluate_king_safety(position, Color::Black, params);
+    let white_activity = evaluate_activity(position, Color::White, params);
+    let black_activity = evaluate_activity(position, Color::Black, params);
+
+    let term = |name, white, black| TraceTerm { name, white, black };
+
+    EvalTrace {
+        terms: vec![
+            term("material", material(white, params), material(black, params)),
+            term(
+                "piece squares",
+                piece_squares(white, params),
+                piece_squares(black, params),
+            ),
+            term("doubled pawns", white_pawns.doubled, black_pawns.doubled),
+            term("isolated pawns", white_pawns.isolated, black_pawns.isolated),
+            term("backward pawns", white_pawns.backward, black_pawns.backward),
+            term(
+                "connected pawns",
+                white_pawns.connected,
+                black_pawns.connected,
+            ),
+            term("passed pawns", white_pawns.passed, black_pawns.passed),
+            term(
+                "candidate pawns",
+                white_pawns.candidate,
+                black_pawns.candidate,
+            ),
+            term("king attacks", white_king.attacks, black_king.attacks),
+            term(
+                "safe checks",
+                white_king.safe_checks,
+                black_king.safe_checks,
+            ),
+            term("pawn shield", white_king.shield, black_king.shield),
+            term("pawn storm", white_king.storm, black_king.storm),
+            term("king files", white_king.open_files, black_king.open_files),
+            term("mobility", white_activity.mobility, black_activity.mobility),
+            term("outposts", white_activity.outposts, black_activity.outposts),
+            term(
+                "rook files",
+                white_activity.rook_files,
+                black_activity.rook_files,
+            ),
+            term(
+                "seventh rank",
+                white_activity.seventh_rank,
+                black_activity.seventh_rank,
+            ),
+            term(
+                "bishop pair",
+                white_activity.bishop_pair,
+                black_activity.bishop_pair,
+            ),
+        ],
+        phase: game_phase(position),
+        side_to_move: position.side_to_move,
+    }
+}
+
+impl fmt::Display for EvalTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<16}|{:>7}{:>7} |{:>7}{:>7} |{:>7}{:>7}",
+            "term", "white", "", "black", "", "total", ""
+        )?;
+        writeln!(
+            f,
+            "{:<16}|{:>7}{:>7} |{:>7}{:>7} |{:>7}{:>7}",
+            "", "mg", "eg", "mg", "eg", "mg", "eg"
+        )?;
+        writeln!(f, "{}", "-".repeat(62))?;
+
+        for term in &self.terms {
+            let total = term.total();
+            writeln!(
+                f,
+                "{:<16}|{:>7}{:>7} |{:>7}{:>7} |{:>7}{:>7}",
+                term.name,
+                term.white.mg,
+                term.white.eg,
+                term.black.mg,
+                term.black.eg,
+                total.mg,
+                total.eg
+            )?;
+        }
+
+        let total = self.total();
+        writeln!(f, "{}", "-".repeat(62))?;
+        writeln!(
+            f,
+            "{:<16}|{:>14} |{:>14} |{:>7}{:>7}",
+            "total", "", "", total.mg, total.eg
+        )?;
+        writeln!(f, "phase {}/{}", self.phase, MAX_PHASE)?;
+        writeln!(f, "white {:+}", self.white_score())?;
+        write!(f, "side to move {:+}", self.score())
+    }
+}
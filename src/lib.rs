@@ -1,3 +1,15 @@
 pub mod bitboard;
+pub mod book_builder;
+pub mod eval;
+pub mod movegen;
+pub mod nnue;
+pub mod pgn;
 pub mod player;
+pub mod polyglot;
+pub mod position;
 pub mod rendering;
+pub mod san;
+pub mod search;
+pub mod see;
+pub mod training_data;
+pub mod zobrist;
@@ -0,0 +1,401 @@
+use crate::{
+    bitboard::{
+        bishop_attacks, move_masks, possible_moves_bishop, possible_moves_queen,
+        possible_moves_rook, queen_attacks, rook_attacks, Bitboard, Direction, RANK_1, RANK_2,
+        RANK_7, RANK_8,
+    },
+    player::{Color, PieceType},
+    position::{
+        Move, MoveKind, Position, BLACK_KINGSIDE, BLACK_QUEENSIDE, WHITE_KINGSIDE,
+        WHITE_QUEENSIDE,
+    },
+};
+
+const PROMOTION_PIECES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Knight,
+    PieceType::Rook,
+    PieceType::Bishop,
+];
+
+// pushes a move for every set bit in destinations
+fn add_moves(moves: &mut Vec<Move>, from: usize, mut destinations: u64) {
+    while destinations != 0 {
+        let to = destinations.trailing_zeros() as usize;
+        moves.push(Move::new(from, to));
+        destinations &= destinations - 1;
+    }
+}
+
+fn add_pawn_moves(moves: &mut Vec<Move>, from: usize, mut destinations: u64) {
+    while destinations != 0 {
+        let to = destinations.trailing_zeros() as usize;
+
+        if (1 << to) & (RANK_1 | RANK_8) > 0 {
+            for promotion in PROMOTION_PIECES {
+                moves.push(Move {
+                    promotion: Some(promotion),
+                    ..Move::new(from, to)
+                });
+            }
+        } else {
+            moves.push(Move::new(from, to));
+        }
+
+        destinations &= destinations - 1;
+    }
+}
+
+impl Position {
+    // captures, en passant and every promotion
+    pub fn generate_captures(&self, moves: &mut Vec<Move>) {
+        self.generate_pseudo_legal_moves(moves, true, false);
+    }
+
+    // everything generate_captures leaves out, castling included
+    pub fn generate_quiets(&self, moves: &mut Vec<Move>) {
+        self.generate_pseudo_legal_moves(moves, false, true);
+    }
+
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(64);
+        self.generate_pseudo_legal_moves(&mut moves, true, true);
+
+        moves
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = self.pseudo_legal_moves();
+        moves.retain(|&pseudo_legal_move| self.is_legal(pseudo_legal_move));
+
+        moves
+    }
+
+    fn generate_pseudo_legal_moves(&self, moves: &mut Vec<Move>, captures: bool, quiets: bool) {
+        let masks = move_masks();
+        let us = self.side_to_move;
+        let friendly_bitboard = self.player(us).all_bitboards();
+        let enemy_bitboard = self.player(us.opposite_color()).all_bitboards();
+        let whole_bitboard = friendly_bitboard | enemy_bitboard;
+        let player = self.player(us);
+
+        let mut targets = 0;
+        if captures {
+            targets |= enemy_bitboard;
+        }
+        if quiets {
+            targets |= !whole_bitboard;
+        }
+
+        self.generate_pawn_moves(moves, captures, quiets);
+
+        let mut knights = player.knight_bitboard;
+        while knights != 0 {
+            let from = knights.trailing_zeros() as usize;
+            add_moves(moves, from, masks.knight_moves[from] & targets);
+            knights &= knights - 1;
+        }
+
+        let mut bishops = player.bishop_bitboard;
+        while bishops != 0 {
+            let from = bishops.trailing_zeros() as usize;
+            let destinations =
+                possible_moves_bishop(enemy_bitboard, whole_bitboard, &masks.bishop_moves, from);
+            add_moves(moves, from, destinations & targets);
+            bishops &= bishops - 1;
+        }
+
+        let mut rooks = player.rook_bitboard;
+        while rooks != 0 {
+            let from = rooks.trailing_zeros() as usize;
+            let destinations =
+                possible_moves_rook(enemy_bitboard, whole_bitboard, &masks.rook_moves, from);
+            add_moves(moves, from, destinations & targets);
+            rooks &= rooks - 1;
+        }
+
+        let mut queens = player.queen_bitboard;
+        while queens != 0 {
+            let from = queens.trailing_zeros() as usize;
+            let destinations =
+                possible_moves_queen(enemy_bitboard, whole_bitboard, &masks.queen_moves, from);
+            add_moves(moves, from, destinations & targets);
+            queens &= queens - 1;
+        }
+
+        let king = self.king_square(us);
+        add_moves(moves, king, masks.king_moves[king] & targets);
+
+        if quiets {
+            self.generate_castling_moves(moves);
+        }
+    }
+
+    fn generate_pawn_moves(&self, moves: &mut Vec<Move>, captures: bool, quiets: bool) {
+        let masks = move_masks();
+        let us = self.side_to_move;
+        let enemy_bitboard = self.player(us.opposite_color()).all_bitboards();
+        let empty_bitboard = !self.occupied_bitboard();
+
+        let (color_index, forward, start_rank) = match us {
+            Color::White => (0, Direction::Up, RANK_2),
+            Color::Black => (1, Direction::Down, RANK_7),
+        };
+
+        let mut pawns = self.player(us).pawn_bitboard;
+        while pawns != 0 {
+            let from = pawns.trailing_zeros() as usize;
+            let current: u64 = 1 << from;
+
+            let single_push = current.shift(forward) & empty_bitboard;
+            let double_push =
+                (single_push & start_rank.shift(forward)).shift(forward) & empty_bitboard;
+            let promotion_push = single_push & (RANK_1 | RANK_8);
+
+            if captures {
+                add_pawn_moves(
+                    moves,
+                    from,
+                    masks.pawn_attacks[color_index][from] & enemy_bitboard,
+                );
+                add_pawn_moves(moves, from, promotion_push);
+
+                if let Some(en_passant_square) = self.en_passant_square {
+                    if masks.pawn_attacks[color_index][from] & (1 << en_passant_square) > 0 {
+                        moves.push(Move {
+                            kind: MoveKind::EnPassant,
+                            ..Move::new(from, en_passant_square)
+                        });
+                    }
+                }
+            }
+
+            if quiets {
+                add_moves(moves, from, (single_push & !promotion_push) | double_push);
+            }
+
+            pawns &= pawns - 1;
+        }
+    }
+
+    fn generate_castling_moves(&self, moves: &mut Vec<Move>) {
+        let us = self.side_to_move;
+        let rights = match us {
+            Color::White => [WHITE_KINGSIDE, WHITE_QUEENSIDE],
+            Color::Black => [BLACK_KINGSIDE, BLACK_QUEENSIDE],
+        };
+
+        for right in rights {
+            if self.castling_rights & right == 0 {
+                continue;
+            }
+
+            let castling_move = Move {
+                kind: MoveKind::Castling,
+                ..Move::new(self.king_square(us), self.castling_rook_square(right))
+            };
+
+            if self.can_castle(castling_move) {
+                moves.push(castling_move);
+            }
+        }
+    }
+
+    // the squares between king, rook and their destinations must be empty and the king may not pass through check
+    fn can_castle(&self, castling_move: Move) -> bool {
+        let us = self.side_to_move;
+        let king_from = castling_move.from;
+        let rook_from = castling_move.to;
+        let king_to = castling_move.king_destination();
+        let rook_to = if rook_from > king_from {
+            king_from / 8 * 8 + 5
+        } else {
+            king_from / 8 * 8 + 3
+        };
+
+        if self.player(us).rook_bitboard & (1 << rook_from) == 0 {
+            return false;
+        }
+
+        let castling_pieces = (1 << king_from) | (1 << rook_from);
+        let occupied_bitboard = self.occupied_bitboard() & !castling_pieces;
+
+        let span = |a: usize, b: usize| -> u64 {
+            (a.min(b)..=a.max(b)).fold(0, |bitboard, square| bitboard | (1 << square))
+        };
+
+        if occupied_bitboard & (span(king_from, king_to) | span(rook_from, rook_to)) > 0 {
+            return false;
+        }
+
+        let enemy_bitboard = self.player(us.opposite_color()).all_bitboards();
+        let mut king_path = span(king_from, king_to);
+        while king_path != 0 {
+            let square = king_path.trailing_zeros() as usize;
+            if self.attackers_to(square, occupied_bitboard) & enemy_bitboard > 0 {
+                return false;
+            }
+            king_path &= king_path - 1;
+        }
+
+        true
+    }
+
+    // whether a pseudo legal move leaves our own king safe
+    pub fn is_legal(&self, pseudo_legal_move: Move) -> bool {
+        // castling checks were already done while generating
+        if pseudo_legal_move.kind == MoveKind::Castling {
+            return true;
+        }
+
+        let us = self.side_to_move;
+        let from_bitboard: u64 = 1 << pseudo_legal_move.from;
+        let to_bitboard: u64 = 1 << pseudo_legal_move.to;
+
+        let mut occupied_bitboard = (self.occupied_bitboard() & !from_bitboard) | to_bitboard;
+        let mut captured_bitboard = to_bitboard;
+
+        if pseudo_legal_move.kind == MoveKind::EnPassant {
+            captured_bitboard = match us {
+                Color::White => to_bitboard >> 8,
+                Color::Black => to_bitboard << 8,
+            };
+            occupied_bitboard &= !captured_bitboard;
+        }
+
+        let king_square = if pseudo_legal_move.from == self.king_square(us) {
+            pseudo_legal_move.to
+        } else {
+            self.king_square(us)
+        };
+
+        let enemy_bitboard = self.player(us.opposite_color()).all_bitboards() & !captured_bitboard;
+
+        self.attackers_to(king_square, occupied_bitboard) & enemy_bitboard == 0
+    }
+
+    // whether candidate is a move the generator could have given here, for moves that come from
+    // somewhere else like the transposition table or a killer slot and may not fit this position
+    pub fn is_pseudo_legal(&self, candidate: Move) -> bool {
+        let us = self.side_to_move;
+        let Some(piece) = self.piece_at(candidate.from) else {
+            return false;
+        };
+        if piece.color() != us {
+            return false;
+        }
+
+        let masks = move_masks();
+        let to_bitboard: u64 = 1 << candidate.to;
+
+        match candidate.kind {
+            MoveKind::Castling => {
+                let rights = match us {
+                    Color::White => [WHITE_KINGSIDE, WHITE_QUEENSIDE],
+                    Color::Black => [BLACK_KINGSIDE, BLACK_QUEENSIDE],
+                };
+
+                piece.piece_type() == PieceType::King
+                    && candidate.promotion.is_none()
+                    && rights.into_iter().any(|right| {
+                        self.castling_rights & right != 0
+                            && self.castling_rook_square(right) == candidate.to
+                    })
+                    && self.can_castle(candidate)
+            }
+            MoveKind::EnPassant => {
+                piece.piece_type() == PieceType::Pawn
+                    && candidate.promotion.is_none()
+                    && self.en_passant_square == Some(candidate.to)
+                    && masks.pawn_attacks[us as usize][candidate.from] & to_bitboard > 0
+            }
+            MoveKind::Normal => {
+                let friendly_bitboard = self.player(us).all_bitboards();
+                let enemy_bitboard = self.player(us.opposite_color()).all_bitboards();
+                let occupied_bitboard = friendly_bitboard | enemy_bitboard;
+
+                if friendly_bitboard & to_bitboard > 0 {
+                    return false;
+                }
+
+                let from = candidate.from;
+                let destinations = match piece.piece_type() {
+                    PieceType::Pawn => {
+                        let promotes = to_bitboard & (RANK_1 | RANK_8) > 0;
+                        let valid_promotion = match candidate.promotion {
+                            Some(PieceType::Pawn | PieceType::King) => false,
+                            promotion => promotion.is_some() == promotes,
+                        };
+                        if !valid_promotion {
+                            return false;
+                        }
+
+                        let (forward, start_rank) = match us {
+                            Color::White => (Direction::Up, RANK_2),
+                            Color::Black => (Direction::Down, RANK_7),
+                        };
+                        let single_push = (1u64 << from).shift(forward) & !occupied_bitboard;
+                        let double_push = (single_push & start_rank.shift(forward)).shift(forward)
+                            & !occupied_bitboard;
+
+                        (masks.pawn_attacks[us as usize][from] & enemy_bitboard)
+                            | single_push
+                            | double_push
+                    }
+                    _ if candidate.promotion.is_some() => return false,
+                    PieceType::Knight => masks.knight_moves[from],
+                    PieceType::Bishop => bishop_attacks(occupied_bitboard, from),
+                    PieceType::Rook => rook_attacks(occupied_bitboard, from),
+                    PieceType::Queen => queen_attacks(occupied_bitboard, from),
+                    PieceType::King => masks.king_moves[from],
+                };
+
+                destinations & to_bitboard > 0
+            }
+        }
+    }
+
+    // castling never counts, the rook on the to square is our own
+    pub fn is_capture(&self, candidate: Move) -> bool {
+        candidate.kind == MoveKind::EnPassant
+            || (candidate.kind == MoveKind::Normal && self.piece_at(candidate.to).is_some())
+    }
+
+    // accepts both e1g1 and e1h1 style castling
+    pub fn move_from_uci(&self, uci: &str) -> Option<Move> {
+        // castling is also taken as king takes rook outside chess960, some guis send it that way
+        self.legal_moves().into_iter().find(|legal_move| {
+            legal_move.to_uci(self.chess960) == uci
+                || (legal_move.kind == MoveKind::Castling && legal_move.to_uci(true) == uci)
+        })
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.in_check() && self.legal_moves().is_empty()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.in_check() && self.legal_moves().is_empty()
+    }
+
+    // count leaf nodes, the standard way of checking move generation against known numbers
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for legal_move in moves {
+            self.make_move(legal_move);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+
+        nodes
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use crate::{
+    nnue::{feature_index, for_each_feature, simd, Network, HIDDEN_SIZE},
+    player::{Color, Piece},
+    position::Position,
+};
+
+// the first layer before activation for both perspectives, indexed by color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Accumulator {
+    pub values: [[i16; HIDDEN_SIZE]; 2],
+}
+
+impl Accumulator {
+    // built from scratch, every piece on the board added onto the biases
+    pub fn refreshed(network: &Network, position: &Position) -> Self {
+        let mut accumulator = Accumulator {
+            values: [[0; HIDDEN_SIZE]; 2],
+        };
+
+        for perspective in [Color::White, Color::Black] {
+            accumulator.refresh(network, position, perspective);
+        }
+
+        accumulator
+    }
+
+    pub fn refresh(&mut self, network: &Network, position: &Position, perspective: Color) {
+        let values = &mut self.values[perspective as usize];
+        values.copy_from_slice(&network.feature_biases);
+
+        for_each_feature(position, perspective, |feature| {
+            simd::add_assign(values, network.feature_weights(feature));
+        });
+    }
+}
+
+// what a position carries around while nnue is on, the accumulator for every ply made so far
+#[derive(Clone, Debug)]
+pub struct NnueState {
+    pub network: Arc<Network>,
+    accumulators: Vec<Accumulator>,
+}
+
+impl NnueState {
+    pub fn new(network: Arc<Network>, position: &Position) -> Self {
+        let accumulator = Accumulator::refreshed(&network, position);
+
+        NnueState {
+            network,
+            accumulators: vec![accumulator],
+        }
+    }
+
+    pub fn current(&self) -> &Accumulator {
+        self.accumulators.last().unwrap()
+    }
+
+    // called as a move is made, the copy is then updated piece by piece
+    pub fn push(&mut self) {
+        self.accumulators.push(*self.current());
+    }
+
+    pub fn pop(&mut self) {
+        self.accumulators.pop();
+    }
+
+    // king_squares is 64 for a king currently lifted off the board, that side gets refreshed afterwards
+    pub fn update(
+        &mut self,
+        piece: Piece,
+        board_index: usize,
+        king_squares: [usize; 2],
+        add: bool,
+    ) {
+        let accumulator = self.accumulators.last_mut().unwrap();
+
+        for perspective in [Color::White, Color::Black] {
+            let king_square = king_squares[perspective as usize];
+            if king_square >= 64 {
+                continue;
+            }
+
+            if let Some(feature) = feature_index(perspective, king_square, piece, board_index) {
+                let values = &mut accumulator.values[perspective as usize];
+                let weights = self.network.feature_weights(feature);
+
+                if add {
+                    simd::add_assign(values, weights);
+                } else {
+                    simd::sub_assign(values, weights);
+                }
+            }
+        }
+    }
+
+    pub fn refresh(&mut self, position: &Position, perspective: Color) {
+        let accumulator = self.accumulators.last_mut().unwrap();
+        accumulator.refresh(&self.network, position, perspective);
+    }
+}
@@ -0,0 +1,221 @@
+pub mod accumulator;
+pub mod simd;
+
+use std::{fmt, path::Path, sync::Arc};
+
+use crate::{
+    nnue::accumulator::{Accumulator, NnueState},
+    player::{Color, Piece, PieceType},
+    position::Position,
+    search::MATE_BOUND,
+};
+
+// HalfKP, one input for every (own king square, non king piece, square) from each sides point of view
+pub const INPUT_SIZE: usize = 64 * 10 * 64;
+pub const HIDDEN_SIZE: usize = 128;
+
+// the accumulator is kept at QA times the float value, output weights at QB times
+pub const QA: i32 = 255;
+pub const QB: i32 = 64;
+// network output of 1.0 is this many centipawns
+pub const OUTPUT_SCALE: i32 = 400;
+// as much as one saturated output weight can add, a bias past that means a broken network
+pub const MAX_OUTPUT_BIAS: i32 = i16::MAX as i32 * QA;
+// evaluations stay clear of the mate scores whatever the network says
+const MAX_EVAL: i32 = MATE_BOUND - 1;
+
+pub const NETWORK_MAGIC: [u8; 4] = *b"NNUE";
+pub const NETWORK_VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+
+// inputs are seen from perspective, so for black the board is flipped and the colors swapped
+// kings are never inputs themselves, they only pick which set of inputs the other pieces use
+pub fn feature_index(
+    perspective: Color,
+    king_square: usize,
+    piece: Piece,
+    board_index: usize,
+) -> Option<usize> {
+    if piece.piece_type() == PieceType::King {
+        return None;
+    }
+
+    let (king_square, board_index) = match perspective {
+        Color::White => (king_square, board_index),
+        Color::Black => (king_square ^ 56, board_index ^ 56),
+    };
+    let piece_index = piece.piece_type() as usize * 2 + (piece.color() != perspective) as usize;
+
+    Some(king_square * 640 + piece_index * 64 + board_index)
+}
+
+// calls f with every input that is on in position from perspectives point of view
+pub fn for_each_feature(position: &Position, perspective: Color, mut f: impl FnMut(usize)) {
+    let king_square = position.king_square(perspective);
+
+    for piece in Piece::ALL {
+        let mut pieces = position
+            .player(piece.color())
+            .piece_bitboard(piece.piece_type());
+
+        while pieces != 0 {
+            let board_index = pieces.trailing_zeros() as usize;
+            if let Some(feature) = feature_index(perspective, king_square, piece, board_index) {
+                f(feature);
+            }
+            pieces &= pieces - 1;
+        }
+    }
+}
+
+// layout on disk, all little endian:
+// magic, version, input size, hidden size as u32
+// feature weights i16 [INPUT_SIZE][HIDDEN_SIZE], feature biases i16 [HIDDEN_SIZE]
+// output weights i16 [2 * HIDDEN_SIZE] side to move half first, output bias i32
+pub struct Network {
+    pub feature_weights: Vec<i16>,
+    pub feature_biases: Vec<i16>,
+    pub output_weights: Vec<i16>,
+    pub output_bias: i32,
+}
+
+impl fmt::Debug for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Network({}x2 -> 1)", HIDDEN_SIZE)
+    }
+}
+
+impl Network {
+    pub fn zeroed() -> Self {
+        Network {
+            feature_weights: vec![0; INPUT_SIZE * HIDDEN_SIZE],
+            feature_biases: vec![0; HIDDEN_SIZE],
+            output_weights: vec![0; 2 * HIDDEN_SIZE],
+            output_bias: 0,
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let expected_size = HEADER_SIZE + 2 * (INPUT_SIZE * HIDDEN_SIZE + 3 * HIDDEN_SIZE) + 4;
+        if bytes.len() != expected_size {
+            return Err(format!(
+                "network file is {} bytes, expected {}",
+                bytes.len(),
+                expected_size
+            ));
+        }
+
+        let header_value =
+            |index: usize| u32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap());
+
+        if bytes[0..4] != NETWORK_MAGIC {
+            return Err("not a network file".to_owned());
+        }
+        if header_value(1) != NETWORK_VERSION {
+            return Err(format!("unsupported network version {}", header_value(1)));
+        }
+        if header_value(2) as usize != INPUT_SIZE || header_value(3) as usize != HIDDEN_SIZE {
+            return Err(format!(
+                "network is {}x{}, expected {}x{}",
+                header_value(2),
+                header_value(3),
+                INPUT_SIZE,
+                HIDDEN_SIZE
+            ));
+        }
+
+        let mut offset = HEADER_SIZE;
+        let mut read_i16s = |count: usize| {
+            let values = bytes[offset..offset + count * 2]
+                .chunks_exact(2)
+                .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+                .collect::<Vec<i16>>();
+            offset += count * 2;
+            values
+        };
+
+        let feature_weights = read_i16s(INPUT_SIZE * HIDDEN_SIZE);
+        let feature_biases = read_i16s(HIDDEN_SIZE);
+        let output_weights = read_i16s(2 * HIDDEN_SIZE);
+        let output_bias = i32::from_le_bytes(bytes[bytes.len() - 4..].try_into().unwrap());
+        if output_bias.abs() > MAX_OUTPUT_BIAS {
+            return Err(format!("output bias {} out of range", output_bias));
+        }
+
+        Ok(Network {
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(&NETWORK_MAGIC);
+        for value in [NETWORK_VERSION, INPUT_SIZE as u32, HIDDEN_SIZE as u32] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for value in self
+            .feature_weights
+            .iter()
+            .chain(&self.feature_biases)
+            .chain(&self.output_weights)
+        {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.output_bias.to_le_bytes());
+
+        bytes
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let bytes = std::fs::read(&path).map_err(|error| {
+            format!(
+                "could not read network {}: {}",
+                path.as_ref().display(),
+                error
+            )
+        })?;
+
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn feature_weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * HIDDEN_SIZE..(feature + 1) * HIDDEN_SIZE]
+    }
+
+    // centipawns from the point of view of side_to_move
+    pub fn evaluate(&self, accumulator: &Accumulator, side_to_move: Color) -> i32 {
+        let us = &accumulator.values[side_to_move as usize];
+        let them = &accumulator.values[side_to_move.opposite_color() as usize];
+
+        // saturated output weights on both halves add up past what an i32 can take once scaled
+        let output = self.output_bias as i64
+            + simd::clipped_relu_dot(us, &self.output_weights[..HIDDEN_SIZE]) as i64
+            + simd::clipped_relu_dot(them, &self.output_weights[HIDDEN_SIZE..]) as i64;
+
+        // and even then it should never be taken for a mate score
+        let score = output * OUTPUT_SCALE as i64 / (QA * QB) as i64;
+        score.clamp(-MAX_EVAL as i64, MAX_EVAL as i64) as i32
+    }
+}
+
+impl Position {
+    // from here on make and unmake keep the networks accumulator up to date
+    pub fn enable_nnue(&mut self, network: Arc<Network>) {
+        self.nnue = Some(NnueState::new(network, self));
+    }
+
+    pub fn disable_nnue(&mut self) {
+        self.nnue = None;
+    }
+
+    pub fn nnue_eval(&self) -> Option<i32> {
+        self.nnue
+            .as_ref()
+            .map(|nnue| nnue.network.evaluate(nnue.current(), self.side_to_move))
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::nnue::QA;
+
+// the vector routines are used when the cpu has them unless turned off, mostly for comparing against scalar
+static SIMD_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_simd_enabled(enabled: bool) {
+    SIMD_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(target_arch = "x86_64")]
+fn use_avx2() -> bool {
+    SIMD_ENABLED.load(Ordering::Relaxed) && is_x86_feature_detected!("avx2")
+}
+
+// true if evaluation is actually running on the vector routines
+pub fn simd_active() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use_avx2()
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+pub fn add_assign(values: &mut [i16], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if use_avx2() {
+        // safe as avx2 support was just checked
+        return unsafe { avx2::add_assign(values, weights) };
+    }
+
+    scalar::add_assign(values, weights)
+}
+
+pub fn sub_assign(values: &mut [i16], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if use_avx2() {
+        return unsafe { avx2::sub_assign(values, weights) };
+    }
+
+    scalar::sub_assign(values, weights)
+}
+
+// sum of clamp(value, 0, QA) * weight
+pub fn clipped_relu_dot(values: &[i16], weights: &[i16]) -> i32 {
+    #[cfg(target_arch = "x86_64")]
+    if use_avx2() {
+        return unsafe { avx2::clipped_relu_dot(values, weights) };
+    }
+
+    scalar::clipped_relu_dot(values, weights)
+}
+
+pub mod scalar {
+    use super::QA;
+
+    pub fn add_assign(values: &mut [i16], weights: &[i16]) {
+        for (value, weight) in values.iter_mut().zip(weights) {
+            *value = value.wrapping_add(*weight);
+        }
+    }
+
+    pub fn sub_assign(values: &mut [i16], weights: &[i16]) {
+        for (value, weight) in values.iter_mut().zip(weights) {
+            *value = value.wrapping_sub(*weight);
+        }
+    }
+
+    pub fn clipped_relu_dot(values: &[i16], weights: &[i16]) -> i32 {
+        values
+            .iter()
+            .zip(weights)
+            .map(|(value, weight)| (*value as i32).clamp(0, QA) * *weight as i32)
+            .sum()
+    }
+}
+
+// lengths are always HIDDEN_SIZE, a multiple of 16, so there is no tail to handle
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    use super::QA;
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn add_assign(values: &mut [i16], weights: &[i16]) {
+        for (value, weight) in values.chunks_exact_mut(16).zip(weights.chunks_exact(16)) {
+            let sum = _mm256_add_epi16(
+                _mm256_loadu_si256(value.as_ptr() as *const __m256i),
+                _mm256_loadu_si256(weight.as_ptr() as *const __m256i),
+            );
+            _mm256_storeu_si256(value.as_mut_ptr() as *mut __m256i, sum);
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sub_assign(values: &mut [i16], weights: &[i16]) {
+        for (value, weight) in values.chunks_exact_mut(16).zip(weights.chunks_exact(16)) {
+            let difference = _mm256_sub_epi16(
+                _mm256_loadu_si256(value.as_ptr() as *const __m256i),
+                _mm256_loadu_si256(weight.as_ptr() as *const __m256i),
+            );
+            _mm256_storeu_si256(value.as_mut_ptr() as *mut __m256i, difference);
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn clipped_relu_dot(values: &[i16], weights: &[i16]) -> i32 {
+        let zero = _mm256_setzero_si256();
+        let max = _mm256_set1_epi16(QA as i16);
+        let mut sum = _mm256_setzero_si256();
+
+        for (value, weight) in values.chunks_exact(16).zip(weights.chunks_exact(16)) {
+            let value = _mm256_loadu_si256(value.as_ptr() as *const __m256i);
+            let clipped = _mm256_min_epi16(_mm256_max_epi16(value, zero), max);
+            let weight = _mm256_loadu_si256(weight.as_ptr() as *const __m256i);
+            // multiplies pairs into i32 and adds neighbours, 255 * i16 twice still fits
+            sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, weight));
+        }
+
+        let mut lanes = [0i32; 8];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sum);
+        lanes.iter().sum()
+    }
+}
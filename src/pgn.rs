@@ -0,0 +1,179 @@
+use std::io::BufRead;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWin,
+    BlackWin,
+    Draw,
+    Unknown,
+}
+
+impl GameResult {
+    pub fn from_pgn(result: &str) -> Self {
+        match result {
+            "1-0" => GameResult::WhiteWin,
+            "0-1" => GameResult::BlackWin,
+            "1/2-1/2" => GameResult::Draw,
+            _ => GameResult::Unknown,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    // san moves of the main line, comments variations and annotations stripped
+    pub moves: Vec<String>,
+    pub result: GameResult,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+    let value = value.trim().trim_matches('"').replace("\\\"", "\"");
+
+    Some((name.to_owned(), value))
+}
+
+fn parse_movetext(movetext: &str) -> (Vec<String>, GameResult) {
+    let mut moves = Vec::new();
+    let mut result = GameResult::Unknown;
+    let mut variation_depth = 0;
+    let mut chars = movetext.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => variation_depth += 1,
+            // a stray closing bracket must not leave later variations read as the mainline
+            ')' => variation_depth = (variation_depth - 1).max(0),
+            c if c.is_whitespace() => {}
+            c => {
+                let mut token = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "{};()".contains(next) {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+
+                if variation_depth > 0 || token.starts_with('$') {
+                    continue;
+                }
+
+                match token.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        result = GameResult::from_pgn(&token);
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                // move numbers can be stuck to the move e.g. 1.e4 or 3...Nf6
+                let san = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+                if !san.is_empty() {
+                    moves.push(san.to_owned());
+                }
+            }
+        }
+    }
+
+    (moves, result)
+}
+
+// reads games one at a time so large collections dont have to fit in memory
+pub struct PgnReader<R: BufRead> {
+    // split by hand rather than with lines() so a line that is not utf-8, a latin-1 player name
+    // say, gets read lossily instead of ending the whole file
+    lines: std::io::Split<R>,
+    pending_tag: Option<(String, String)>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        PgnReader {
+            lines: reader.split(b'\n'),
+            pending_tag: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = PgnGame;
+
+    fn next(&mut self) -> Option<PgnGame> {
+        let mut tags: Vec<(String, String)> = self.pending_tag.take().into_iter().collect();
+        let mut movetext = String::new();
+
+        for line in self.lines.by_ref() {
+            let Ok(line) = line else {
+                break;
+            };
+            let line = String::from_utf8_lossy(&line);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+
+            if line.trim_start().starts_with('[') && !movetext.trim().is_empty() {
+                // the next games tags, hold on to it for the following call
+                self.pending_tag = parse_tag(line);
+                break;
+            }
+
+            match parse_tag(line) {
+                // a tag this game already has means the next game, this one had no movetext
+                Some(tag) if tags.iter().any(|(name, _)| *name == tag.0) => {
+                    self.pending_tag = Some(tag);
+                    break;
+                }
+                Some(tag) if movetext.trim().is_empty() => tags.push(tag),
+                _ => {
+                    movetext.push_str(line);
+                    movetext.push('\n');
+                }
+            }
+        }
+
+        if tags.is_empty() && movetext.trim().is_empty() {
+            return None;
+        }
+
+        let (moves, movetext_result) = parse_movetext(&movetext);
+        let mut game = PgnGame {
+            tags,
+            moves,
+            result: movetext_result,
+        };
+
+        if game.result == GameResult::Unknown {
+            game.result = GameResult::from_pgn(game.tag("Result").unwrap_or("*"));
+        }
+
+        Some(game)
+    }
+}
+
+pub fn parse_pgn(pgn: &str) -> Vec<PgnGame> {
+    PgnReader::new(pgn.as_bytes()).collect()
+}
@@ -1,6 +1,6 @@
 use crate::bitboard::Bitboard;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Hash, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Color {
     White,
     Black,
@@ -16,6 +16,27 @@ impl Color {
     }
 }
 
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PieceType {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl PieceType {
+    pub const ALL: [PieceType; 6] = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+        PieceType::King,
+    ];
+}
+
 #[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum Piece {
     WhitePawn,
@@ -32,6 +53,46 @@ pub enum Piece {
     BlackKing,
 }
 
+impl Piece {
+    pub const ALL: [Piece; 12] = [
+        Piece::WhitePawn,
+        Piece::WhiteKnight,
+        Piece::WhiteBishop,
+        Piece::WhiteRook,
+        Piece::WhiteQueen,
+        Piece::WhiteKing,
+        Piece::BlackPawn,
+        Piece::BlackKnight,
+        Piece::BlackBishop,
+        Piece::BlackRook,
+        Piece::BlackQueen,
+        Piece::BlackKing,
+    ];
+
+    pub fn new(color: Color, piece_type: PieceType) -> Self {
+        // pieces are laid out white then black in the same order as PieceType
+        let offset = match color {
+            Color::White => 0,
+            Color::Black => 6,
+        };
+
+        Piece::ALL[offset + piece_type as usize]
+    }
+
+    pub fn color(&self) -> Color {
+        if (*self as usize) < 6 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    pub fn piece_type(&self) -> PieceType {
+        PieceType::ALL[*self as usize % 6]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Player {
     pub color: Color,
     pub pawn_bitboard: u64,
@@ -73,6 +134,40 @@ impl Player {
         }
     }
 
+    pub fn empty(color: Color) -> Self {
+        Player {
+            color,
+            pawn_bitboard: 0,
+            knight_bitboard: 0,
+            bishop_bitboard: 0,
+            rook_bitboard: 0,
+            queen_bitboard: 0,
+            king_bitboard: 0,
+        }
+    }
+
+    pub fn piece_bitboard(&self, piece_type: PieceType) -> u64 {
+        match piece_type {
+            PieceType::Pawn => self.pawn_bitboard,
+            PieceType::Knight => self.knight_bitboard,
+            PieceType::Bishop => self.bishop_bitboard,
+            PieceType::Rook => self.rook_bitboard,
+            PieceType::Queen => self.queen_bitboard,
+            PieceType::King => self.king_bitboard,
+        }
+    }
+
+    pub fn piece_bitboard_mut(&mut self, piece_type: PieceType) -> &mut u64 {
+        match piece_type {
+            PieceType::Pawn => &mut self.pawn_bitboard,
+            PieceType::Knight => &mut self.knight_bitboard,
+            PieceType::Bishop => &mut self.bishop_bitboard,
+            PieceType::Rook => &mut self.rook_bitboard,
+            PieceType::Queen => &mut self.queen_bitboard,
+            PieceType::King => &mut self.king_bitboard,
+        }
+    }
+
     pub fn all_bitboards(&self) -> u64 {
         self.pawn_bitboard
             | self.knight_bitboard
@@ -0,0 +1,979 @@
+use std::fmt;
+
+use crate::{
+    bitboard::{bishop_attacks, move_masks, rook_attacks, Bitboard},
+    nnue::accumulator::NnueState,
+    player::{Color, Piece, PieceType, Player},
+    zobrist::ZOBRIST_KEYS,
+};
+
+pub const WHITE_KINGSIDE: u8 = 0b0001;
+pub const WHITE_QUEENSIDE: u8 = 0b0010;
+pub const BLACK_KINGSIDE: u8 = 0b0100;
+pub const BLACK_QUEENSIDE: u8 = 0b1000;
+pub const ALL_CASTLING_RIGHTS: u8 = 0b1111;
+pub const CASTLING_RIGHTS: [u8; 4] = [
+    WHITE_KINGSIDE,
+    WHITE_QUEENSIDE,
+    BLACK_KINGSIDE,
+    BLACK_QUEENSIDE,
+];
+const STANDARD_CASTLING_ROOKS: [usize; 4] = [7, 0, 63, 56];
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MoveKind {
+    Normal,
+    EnPassant,
+    // castling is stored as the king capturing its own rook, to is the rook square
+    Castling,
+}
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Move {
+    pub from: usize,
+    pub to: usize,
+    pub promotion: Option<PieceType>,
+    pub kind: MoveKind,
+}
+
+impl Move {
+    pub fn new(from: usize, to: usize) -> Self {
+        Move {
+            from,
+            to,
+            promotion: None,
+            kind: MoveKind::Normal,
+        }
+    }
+
+    // the square the king lands on, which for castling is not the to square
+    pub fn king_destination(&self) -> usize {
+        match self.kind {
+            MoveKind::Castling if self.to > self.from => self.from / 8 * 8 + 6,
+            MoveKind::Castling => self.from / 8 * 8 + 2,
+            _ => self.to,
+        }
+    }
+
+    // uci long algebraic, chess960 castling is written as king takes rook e.g. e1h1 instead of e1g1
+    pub fn to_uci(&self, chess960: bool) -> String {
+        let to = if chess960 {
+            self.to
+        } else {
+            self.king_destination()
+        };
+
+        let mut uci = format!("{}{}", square_name(self.from), square_name(to));
+
+        if let Some(promotion) = self.promotion {
+            uci.push(match promotion {
+                PieceType::Knight => 'n',
+                PieceType::Bishop => 'b',
+                PieceType::Rook => 'r',
+                _ => 'q',
+            });
+        }
+
+        uci
+    }
+}
+
+// no game to ask whether it is chess960, so castling that leaves the king where it is gets
+// written king takes rook, the usual form would read as a null move like g1g1
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let king_stays = self.kind == MoveKind::Castling && self.king_destination() == self.from;
+        write!(f, "{}", self.to_uci(king_stays))
+    }
+}
+
+pub fn square_name(board_index: usize) -> String {
+    let file = (b'a' + (board_index % 8) as u8) as char;
+    let rank = (b'1' + (board_index / 8) as u8) as char;
+
+    format!("{}{}", file, rank)
+}
+
+pub fn parse_square(name: &str) -> Option<usize> {
+    let bytes = name.as_bytes();
+    if bytes.len() != 2 || !(b'a'..=b'h').contains(&bytes[0]) || !(b'1'..=b'8').contains(&bytes[1])
+    {
+        return None;
+    }
+
+    Some((bytes[1] - b'1') as usize * 8 + (bytes[0] - b'a') as usize)
+}
+
+// everything make_move destroys, so unmake_move can put it back
+#[derive(Clone, Copy, Debug)]
+pub struct UndoState {
+    pub played_move: Move,
+    pub moved_piece: Piece,
+    pub captured_piece: Option<Piece>,
+    pub castling_rights: u8,
+    pub en_passant_square: Option<usize>,
+    pub halfmove_clock: u32,
+    pub zobrist_key: u64,
+    pub pawn_key: u64,
+}
+
+// what make_null_move changes, handed back to unmake_null_move
+#[derive(Clone, Copy, Debug)]
+pub struct NullMoveUndo {
+    pub en_passant_square: Option<usize>,
+    pub halfmove_clock: u32,
+    pub zobrist_key: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub white_player: Player,
+    pub black_player: Player,
+    pub side_to_move: Color,
+    pub castling_rights: u8,
+    pub en_passant_square: Option<usize>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub zobrist_key: u64,
+    // zobrist key of the pawns alone, for caching pawn structure evaluation
+    pub pawn_key: u64,
+    pub history: Vec<UndoState>,
+    // starting square of the rook each castling right belongs to, in CASTLING_RIGHTS order
+    pub castling_rooks: [usize; 4],
+    pub chess960: bool,
+    // the network accumulators when nnue evaluation is turned on
+    pub nnue: Option<NnueState>,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Position {
+    pub fn new() -> Self {
+        let mut position = Position {
+            white_player: Player::new(Color::White),
+            black_player: Player::new(Color::Black),
+            side_to_move: Color::White,
+            castling_rights: ALL_CASTLING_RIGHTS,
+            en_passant_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            zobrist_key: 0,
+            pawn_key: 0,
+            history: Vec::new(),
+            castling_rooks: STANDARD_CASTLING_ROOKS,
+            chess960: false,
+            nnue: None,
+        };
+        position.zobrist_key = position.compute_zobrist_key();
+        position.pawn_key = position.compute_pawn_key();
+
+        position
+    }
+
+    // scharnagl numbering, index 518 is the normal starting position
+    pub fn chess960(index: usize) -> Self {
+        assert!(index < 960, "chess960 positions are numbered 0 to 959");
+
+        let mut back_rank = [None; 8];
+        let mut index = index;
+
+        // light squared bishop on b d f h, dark squared on a c e g
+        back_rank[(index % 4) * 2 + 1] = Some(PieceType::Bishop);
+        index /= 4;
+        back_rank[(index % 4) * 2] = Some(PieceType::Bishop);
+        index /= 4;
+
+        let mut place_on_empty = |n: usize, piece_type: PieceType| {
+            let file = (0..8)
+                .filter(|&file| back_rank[file].is_none())
+                .nth(n)
+                .unwrap();
+            back_rank[file] = Some(piece_type);
+        };
+
+        place_on_empty(index % 6, PieceType::Queen);
+        index /= 6;
+
+        let (first_knight, second_knight) = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ][index];
+        // place the second knight first so the first knights empty square index is unaffected
+        place_on_empty(second_knight, PieceType::Knight);
+        place_on_empty(first_knight, PieceType::Knight);
+
+        // rook king rook on whatever is left
+        place_on_empty(0, PieceType::Rook);
+        place_on_empty(0, PieceType::King);
+        place_on_empty(0, PieceType::Rook);
+
+        let mut white_player = Player::empty(Color::White);
+        for (file, piece_type) in back_rank.iter().enumerate() {
+            *white_player.piece_bitboard_mut(piece_type.unwrap()) |= 1 << file;
+        }
+        white_player.pawn_bitboard = 0xff00;
+
+        let mut black_player = Player::empty(Color::Black);
+        for piece_type in PieceType::ALL {
+            *black_player.piece_bitboard_mut(piece_type) = white_player
+                .piece_bitboard(piece_type)
+                .flip_over_horizontal();
+        }
+
+        let rooks = white_player.rook_bitboard;
+        let queenside_rook = rooks.trailing_zeros() as usize;
+        let kingside_rook = 63 - rooks.leading_zeros() as usize;
+
+        let mut position = Position {
+            white_player,
+            black_player,
+            side_to_move: Color::White,
+            castling_rights: ALL_CASTLING_RIGHTS,
+            en_passant_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            zobrist_key: 0,
+            pawn_key: 0,
+            history: Vec::new(),
+            castling_rooks: [
+                kingside_rook,
+                queenside_rook,
+                kingside_rook + 56,
+                queenside_rook + 56,
+            ],
+            chess960: true,
+            nnue: None,
+        };
+        position.zobrist_key = position.compute_zobrist_key();
+        position.pawn_key = position.compute_pawn_key();
+
+        position
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let mut fields = fen.split_whitespace();
+
+        let placement = fields.next().ok_or("missing piece placement")?;
+        let side_to_move = fields.next().unwrap_or("w");
+        let castling = fields.next().unwrap_or("-");
+        let en_passant = fields.next().unwrap_or("-");
+        let halfmove_clock = fields.next().unwrap_or("0");
+        let fullmove_number = fields.next().unwrap_or("1");
+
+        let mut position = Position {
+            white_player: Player::empty(Color::White),
+            black_player: Player::empty(Color::Black),
+            side_to_move: Color::White,
+            castling_rights: 0,
+            en_passant_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            zobrist_key: 0,
+            pawn_key: 0,
+            history: Vec::new(),
+            castling_rooks: STANDARD_CASTLING_ROOKS,
+            chess960: false,
+            nnue: None,
+        };
+
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("expected 8 ranks, found {}", ranks.len()));
+        }
+
+        // fen lists rank 8 first
+        for (i, rank_str) in ranks.iter().enumerate() {
+            let rank = 7 - i;
+            let mut file = 0;
+
+            for c in rank_str.chars() {
+                if let Some(skip) = c.to_digit(10) {
+                    file += skip as usize;
+                    continue;
+                }
+
+                if file > 7 {
+                    return Err(format!("rank {} has more than 8 files", rank + 1));
+                }
+
+                let piece = piece_from_char(c).ok_or(format!("unknown piece '{}'", c))?;
+                position.put_piece(piece, rank * 8 + file);
+                file += 1;
+            }
+
+            if file != 8 {
+                return Err(format!("rank {} does not have 8 files", rank + 1));
+            }
+        }
+
+        position.side_to_move = match side_to_move {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(format!("unknown side to move '{}'", other)),
+        };
+
+        if position.white_player.king_bitboard.count_ones() != 1
+            || position.black_player.king_bitboard.count_ones() != 1
+        {
+            return Err("each side needs exactly one king".to_owned());
+        }
+
+        // KQkq pick the outermost rook like x-fen, file letters like shredder-fen name the rook directly
+        for c in castling.chars() {
+            if c == '-' {
+                continue;
+            }
+
+            let color = if c.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let back_rank = match color {
+                Color::White => 0,
+                Color::Black => 56,
+            };
+            // castling rights with the king anywhere but its back rank make no sense
+            if position.king_square(color) / 8 != back_rank / 8 {
+                return Err(format!("castling right '{}' without the king on its back rank", c));
+            }
+
+            let king_file = position.king_square(color) % 8;
+            let rooks = position.player(color).rook_bitboard;
+            let has_rook = |file: usize| rooks & (1 << (back_rank + file)) > 0;
+
+            let rook_file = match c.to_ascii_lowercase() {
+                'k' => (king_file + 1..8).rev().find(|&file| has_rook(file)),
+                'q' => (0..king_file).find(|&file| has_rook(file)),
+                file @ 'a'..='h' => {
+                    position.chess960 = true;
+                    Some(file as usize - 'a' as usize).filter(|&file| has_rook(file))
+                }
+                _ => return Err(format!("unknown castling right '{}'", c)),
+            }
+            .ok_or(format!("no rook for castling right '{}'", c))?;
+
+            let right = match (color, rook_file > king_file) {
+                (Color::White, true) => WHITE_KINGSIDE,
+                (Color::White, false) => WHITE_QUEENSIDE,
+                (Color::Black, true) => BLACK_KINGSIDE,
+                (Color::Black, false) => BLACK_QUEENSIDE,
+            };
+
+            position.castling_rights |= right;
+            position.castling_rooks[right.trailing_zeros() as usize] = back_rank + rook_file;
+
+            // a king or rook off its usual square can only mean fischer random
+            if king_file != 4 || (rook_file != 0 && rook_file != 7) {
+                position.chess960 = true;
+            }
+        }
+
+        position.en_passant_square = match en_passant {
+            "-" => None,
+            square => Some(
+                parse_square(square).ok_or(format!("invalid en passant square '{}'", square))?,
+            ),
+        };
+
+        position.halfmove_clock = halfmove_clock
+            .parse()
+            .map_err(|_| format!("invalid halfmove clock '{}'", halfmove_clock))?;
+        position.fullmove_number = fullmove_number
+            .parse()
+            .map_err(|_| format!("invalid fullmove number '{}'", fullmove_number))?;
+
+        position.zobrist_key = position.compute_zobrist_key();
+        position.pawn_key = position.compute_pawn_key();
+
+        Ok(position)
+    }
+
+    // x-fen, only falls back to rook files when KQkq would be ambiguous
+    pub fn to_fen(&self) -> String {
+        self.fen_with_castling(false)
+    }
+
+    pub fn to_shredder_fen(&self) -> String {
+        self.fen_with_castling(true)
+    }
+
+    fn fen_with_castling(&self, shredder: bool) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+
+            for file in 0..8 {
+                match self.piece_at(rank * 8 + file) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece_to_char(piece));
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push_str(match self.side_to_move {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+
+        if self.castling_rights == 0 {
+            fen.push('-');
+        }
+        for (right, c) in CASTLING_RIGHTS.into_iter().zip(['K', 'Q', 'k', 'q']) {
+            if self.castling_rights & right == 0 {
+                continue;
+            }
+
+            let rook_square = self.castling_rook_square(right);
+            let color = if right & (WHITE_KINGSIDE | WHITE_QUEENSIDE) != 0 {
+                Color::White
+            } else {
+                Color::Black
+            };
+
+            // any other rook further out on the same side would steal the KQkq letter
+            let king_square = self.king_square(color);
+            let outer_rooks = (0..8)
+                .map(|file| rook_square / 8 * 8 + file)
+                .filter(|&square| {
+                    square != rook_square
+                        && (square > king_square) == (rook_square > king_square)
+                        && (square > rook_square) == (rook_square > king_square)
+                })
+                .any(|square| self.player(color).rook_bitboard & (1 << square) > 0);
+
+            if shredder || outer_rooks {
+                let file = (b'a' + (rook_square % 8) as u8) as char;
+                fen.push(match color {
+                    Color::White => file.to_ascii_uppercase(),
+                    Color::Black => file,
+                });
+            } else {
+                fen.push(c);
+            }
+        }
+
+        match self.en_passant_square {
+            Some(square) => fen.push_str(&format!(" {}", square_name(square))),
+            None => fen.push_str(" -"),
+        }
+
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+
+        fen
+    }
+
+    pub fn player(&self, color: Color) -> &Player {
+        match color {
+            Color::White => &self.white_player,
+            Color::Black => &self.black_player,
+        }
+    }
+
+    pub fn player_mut(&mut self, color: Color) -> &mut Player {
+        match color {
+            Color::White => &mut self.white_player,
+            Color::Black => &mut self.black_player,
+        }
+    }
+
+    pub fn occupied_bitboard(&self) -> u64 {
+        self.white_player.all_bitboards() | self.black_player.all_bitboards()
+    }
+
+    pub fn piece_at(&self, board_index: usize) -> Option<Piece> {
+        let current = 1 << board_index;
+
+        for player in [&self.white_player, &self.black_player] {
+            if player.all_bitboards() & current == 0 {
+                continue;
+            }
+
+            for piece_type in PieceType::ALL {
+                if player.piece_bitboard(piece_type) & current > 0 {
+                    return Some(Piece::new(player.color, piece_type));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn king_square(&self, color: Color) -> usize {
+        self.player(color).king_bitboard.trailing_zeros() as usize
+    }
+
+    // every piece of either color attacking board_index, given some occupancy for the sliders
+    pub fn attackers_to(&self, board_index: usize, occupied_bitboard: u64) -> u64 {
+        let masks = move_masks();
+        let white = &self.white_player;
+        let black = &self.black_player;
+
+        let bishops_queens = white.bishop_bitboard
+            | white.queen_bitboard
+            | black.bishop_bitboard
+            | black.queen_bitboard;
+        let rooks_queens =
+            white.rook_bitboard | white.queen_bitboard | black.rook_bitboard | black.queen_bitboard;
+
+        // a white pawn attacks this square if a black pawn here would attack the white pawn
+        (masks.pawn_attacks[1][board_index] & white.pawn_bitboard)
+            | (masks.pawn_attacks[0][board_index] & black.pawn_bitboard)
+            | (masks.knight_moves[board_index] & (white.knight_bitboard | black.knight_bitboard))
+            | (masks.king_moves[board_index] & (white.king_bitboard | black.king_bitboard))
+            | (bishop_attacks(occupied_bitboard, board_index) & bishops_queens)
+            | (rook_attacks(occupied_bitboard, board_index) & rooks_queens)
+    }
+
+    pub fn is_square_attacked(&self, board_index: usize, by_color: Color) -> bool {
+        self.attackers_to(board_index, self.occupied_bitboard())
+            & self.player(by_color).all_bitboards()
+            > 0
+    }
+
+    pub fn in_check(&self) -> bool {
+        self.is_square_attacked(
+            self.king_square(self.side_to_move),
+            self.side_to_move.opposite_color(),
+        )
+    }
+
+    // whether this position already came up since the last capture or pawn move, only the same side to move can match
+    pub fn is_repetition(&self) -> bool {
+        self.history
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|undo| undo.zobrist_key == self.zobrist_key)
+    }
+
+    // kings of both sides for the nnue inputs, 64 if a king is off the board partway through a move
+    fn king_squares(&self) -> [usize; 2] {
+        [
+            self.white_player.king_bitboard.trailing_zeros() as usize,
+            self.black_player.king_bitboard.trailing_zeros() as usize,
+        ]
+    }
+
+    fn put_piece(&mut self, piece: Piece, board_index: usize) {
+        *self
+            .player_mut(piece.color())
+            .piece_bitboard_mut(piece.piece_type()) |= 1 << board_index;
+        let king_squares = self.king_squares();
+        if let Some(nnue) = &mut self.nnue {
+            nnue.update(piece, board_index, king_squares, true);
+        }
+        self.zobrist_key ^= ZOBRIST_KEYS.piece(piece, board_index);
+        if piece.piece_type() == PieceType::Pawn {
+            self.pawn_key ^= ZOBRIST_KEYS.piece(piece, board_index);
+        }
+    }
+
+    fn remove_piece(&mut self, piece: Piece, board_index: usize) {
+        *self
+            .player_mut(piece.color())
+            .piece_bitboard_mut(piece.piece_type()) &= !(1 << board_index);
+        let king_squares = self.king_squares();
+        if let Some(nnue) = &mut self.nnue {
+            nnue.update(piece, board_index, king_squares, false);
+        }
+        self.zobrist_key ^= ZOBRIST_KEYS.piece(piece, board_index);
+        if piece.piece_type() == PieceType::Pawn {
+            self.pawn_key ^= ZOBRIST_KEYS.piece(piece, board_index);
+        }
+    }
+
+    fn set_en_passant_square(&mut self, en_passant_square: Option<usize>) {
+        if let Some(square) = self.en_passant_square {
+            self.zobrist_key ^= ZOBRIST_KEYS.en_passant_file[square % 8];
+        }
+        if let Some(square) = en_passant_square {
+            self.zobrist_key ^= ZOBRIST_KEYS.en_passant_file[square % 8];
+        }
+        self.en_passant_square = en_passant_square;
+    }
+
+    fn set_castling_rights(&mut self, castling_rights: u8) {
+        self.zobrist_key ^= ZOBRIST_KEYS.castling[self.castling_rights as usize];
+        self.zobrist_key ^= ZOBRIST_KEYS.castling[castling_rights as usize];
+        self.castling_rights = castling_rights;
+    }
+
+    // the rook square a castling right belongs to
+    pub fn castling_rook_square(&self, castling_right: u8) -> usize {
+        self.castling_rooks[castling_right.trailing_zeros() as usize]
+    }
+
+    // rights that disappear once something moves from or to board_index
+    fn castling_rights_lost(&self, board_index: usize) -> u8 {
+        let mut lost = 0;
+
+        if board_index == self.king_square(Color::White) {
+            lost |= WHITE_KINGSIDE | WHITE_QUEENSIDE;
+        }
+        if board_index == self.king_square(Color::Black) {
+            lost |= BLACK_KINGSIDE | BLACK_QUEENSIDE;
+        }
+        for right in CASTLING_RIGHTS {
+            if board_index == self.castling_rook_square(right) {
+                lost |= right;
+            }
+        }
+
+        lost
+    }
+
+    pub fn make_move(&mut self, played_move: Move) {
+        let us = self.side_to_move;
+        let moved_piece = self
+            .piece_at(played_move.from)
+            .expect("no piece on the from square");
+
+        let captured_piece = match played_move.kind {
+            MoveKind::Normal => self.piece_at(played_move.to),
+            MoveKind::EnPassant => Some(Piece::new(us.opposite_color(), PieceType::Pawn)),
+            MoveKind::Castling => None,
+        };
+
+        self.history.push(UndoState {
+            played_move,
+            moved_piece,
+            captured_piece,
+            castling_rights: self.castling_rights,
+            en_passant_square: self.en_passant_square,
+            halfmove_clock: self.halfmove_clock,
+            zobrist_key: self.zobrist_key,
+            pawn_key: self.pawn_key,
+        });
+
+        if let Some(nnue) = &mut self.nnue {
+            nnue.push();
+        }
+
+        // work out lost rights before any pieces move, the king square lookup needs the king in place
+        let castling_rights = self.castling_rights
+            & !self.castling_rights_lost(played_move.from)
+            & !self.castling_rights_lost(played_move.to);
+
+        self.set_en_passant_square(None);
+
+        match played_move.kind {
+            MoveKind::Castling => {
+                let rook = Piece::new(us, PieceType::Rook);
+                let rank_start = played_move.from / 8 * 8;
+                let rook_destination = if played_move.to > played_move.from {
+                    rank_start + 5
+                } else {
+                    rank_start + 3
+                };
+
+                // take both off before putting back as the squares may overlap
+                self.remove_piece(moved_piece, played_move.from);
+                self.remove_piece(rook, played_move.to);
+                self.put_piece(moved_piece, played_move.king_destination());
+                self.put_piece(rook, rook_destination);
+            }
+            MoveKind::EnPassant => {
+                // the captured pawn sits behind the square we land on
+                let captured_square = match us {
+                    Color::White => played_move.to - 8,
+                    Color::Black => played_move.to + 8,
+                };
+
+                self.remove_piece(captured_piece.unwrap(), captured_square);
+                self.remove_piece(moved_piece, played_move.from);
+                self.put_piece(moved_piece, played_move.to);
+            }
+            MoveKind::Normal => {
+                if let Some(captured_piece) = captured_piece {
+                    self.remove_piece(captured_piece, played_move.to);
+                }
+
+                self.remove_piece(moved_piece, played_move.from);
+                match played_move.promotion {
+                    Some(promotion) => self.put_piece(Piece::new(us, promotion), played_move.to),
+                    None => self.put_piece(moved_piece, played_move.to),
+                }
+
+                if moved_piece.piece_type() == PieceType::Pawn
+                    && played_move.from.abs_diff(played_move.to) == 16
+                {
+                    self.set_en_passant_square(Some((played_move.from + played_move.to) / 2));
+                }
+            }
+        }
+
+        self.set_castling_rights(castling_rights);
+
+        if moved_piece.piece_type() == PieceType::Pawn || captured_piece.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if us == Color::Black {
+            self.fullmove_number += 1;
+        }
+
+        self.side_to_move = us.opposite_color();
+        self.zobrist_key ^= ZOBRIST_KEYS.black_to_move;
+
+        // every input of our perspective depends on where our king is
+        if moved_piece.piece_type() == PieceType::King {
+            if let Some(mut nnue) = self.nnue.take() {
+                nnue.refresh(self, us);
+                self.nnue = Some(nnue);
+            }
+        }
+    }
+
+    pub fn unmake_move(&mut self) {
+        let undo = self.history.pop().expect("no move to unmake");
+        let played_move = undo.played_move;
+
+        // the accumulator from before the move is still on the stack, no need to update it piece by piece
+        let nnue = self.nnue.take();
+
+        self.side_to_move = self.side_to_move.opposite_color();
+        let us = self.side_to_move;
+
+        if us == Color::Black {
+            self.fullmove_number -= 1;
+        }
+
+        match played_move.kind {
+            MoveKind::Castling => {
+                let rook = Piece::new(us, PieceType::Rook);
+                let rank_start = played_move.from / 8 * 8;
+                let rook_destination = if played_move.to > played_move.from {
+                    rank_start + 5
+                } else {
+                    rank_start + 3
+                };
+
+                self.remove_piece(undo.moved_piece, played_move.king_destination());
+                self.remove_piece(rook, rook_destination);
+                self.put_piece(undo.moved_piece, played_move.from);
+                self.put_piece(rook, played_move.to);
+            }
+            MoveKind::EnPassant => {
+                let captured_square = match us {
+                    Color::White => played_move.to - 8,
+                    Color::Black => played_move.to + 8,
+                };
+
+                self.remove_piece(undo.moved_piece, played_move.to);
+                self.put_piece(undo.moved_piece, played_move.from);
+                self.put_piece(undo.captured_piece.unwrap(), captured_square);
+            }
+            MoveKind::Normal => {
+                let placed_piece = match played_move.promotion {
+                    Some(promotion) => Piece::new(us, promotion),
+                    None => undo.moved_piece,
+                };
+
+                self.remove_piece(placed_piece, played_move.to);
+                self.put_piece(undo.moved_piece, played_move.from);
+
+                if let Some(captured_piece) = undo.captured_piece {
+                    self.put_piece(captured_piece, played_move.to);
+                }
+            }
+        }
+
+        self.castling_rights = undo.castling_rights;
+        self.en_passant_square = undo.en_passant_square;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.zobrist_key = undo.zobrist_key;
+        self.pawn_key = undo.pawn_key;
+
+        self.nnue = nnue.map(|mut nnue| {
+            nnue.pop();
+            nnue
+        });
+    }
+
+    // passes the turn without moving anything, only the search does this, for null move pruning
+    // the halfmove clock starts again so repetition checks never look back past the null move
+    pub fn make_null_move(&mut self) -> NullMoveUndo {
+        let undo = NullMoveUndo {
+            en_passant_square: self.en_passant_square,
+            halfmove_clock: self.halfmove_clock,
+            zobrist_key: self.zobrist_key,
+        };
+
+        // nothing moved so the accumulator stays the same, but the stack has to stay in step with the plies
+        if let Some(nnue) = &mut self.nnue {
+            nnue.push();
+        }
+
+        self.set_en_passant_square(None);
+        self.halfmove_clock = 0;
+        self.side_to_move = self.side_to_move.opposite_color();
+        self.zobrist_key ^= ZOBRIST_KEYS.black_to_move;
+
+        undo
+    }
+
+    pub fn unmake_null_move(&mut self, undo: NullMoveUndo) {
+        self.side_to_move = self.side_to_move.opposite_color();
+        self.en_passant_square = undo.en_passant_square;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.zobrist_key = undo.zobrist_key;
+
+        if let Some(nnue) = &mut self.nnue {
+            nnue.pop();
+        }
+    }
+
+    // the same position with colors swapped and the board flipped top to bottom, history is dropped
+    pub fn mirrored(&self) -> Position {
+        let mirror_player = |player: &Player| {
+            let mut mirrored = Player::empty(player.color.opposite_color());
+            for piece_type in PieceType::ALL {
+                *mirrored.piece_bitboard_mut(piece_type) =
+                    player.piece_bitboard(piece_type).flip_over_horizontal();
+            }
+            mirrored
+        };
+
+        let castling_rooks = self.castling_rooks;
+
+        let mut position = Position {
+            white_player: mirror_player(&self.black_player),
+            black_player: mirror_player(&self.white_player),
+            side_to_move: self.side_to_move.opposite_color(),
+            // white rights are the low two bits, black the high two
+            castling_rights: (self.castling_rights >> 2) | ((self.castling_rights & 0b11) << 2),
+            en_passant_square: self.en_passant_square.map(|square| square ^ 56),
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            zobrist_key: 0,
+            pawn_key: 0,
+            history: Vec::new(),
+            castling_rooks: [
+                castling_rooks[2] ^ 56,
+                castling_rooks[3] ^ 56,
+                castling_rooks[0] ^ 56,
+                castling_rooks[1] ^ 56,
+            ],
+            chess960: self.chess960,
+            nnue: None,
+        };
+        position.zobrist_key = position.compute_zobrist_key();
+        position.pawn_key = position.compute_pawn_key();
+
+        position
+    }
+
+    pub fn compute_pawn_key(&self) -> u64 {
+        let mut key = 0;
+
+        for piece in [Piece::WhitePawn, Piece::BlackPawn] {
+            let mut bitboard = self.player(piece.color()).pawn_bitboard;
+
+            while bitboard != 0 {
+                let board_index = bitboard.trailing_zeros() as usize;
+                key ^= ZOBRIST_KEYS.piece(piece, board_index);
+                bitboard &= bitboard - 1;
+            }
+        }
+
+        key
+    }
+
+    // build the key from nothing, for setting up positions and checking the incremental one
+    pub fn compute_zobrist_key(&self) -> u64 {
+        let mut key = 0;
+
+        for piece in Piece::ALL {
+            let mut bitboard = self
+                .player(piece.color())
+                .piece_bitboard(piece.piece_type());
+
+            while bitboard != 0 {
+                let board_index = bitboard.trailing_zeros() as usize;
+                key ^= ZOBRIST_KEYS.piece(piece, board_index);
+                bitboard &= bitboard - 1;
+            }
+        }
+
+        key ^= ZOBRIST_KEYS.side(self.side_to_move);
+        key ^= ZOBRIST_KEYS.castling[self.castling_rights as usize];
+
+        if let Some(square) = self.en_passant_square {
+            key ^= ZOBRIST_KEYS.en_passant_file[square % 8];
+        }
+
+        key
+    }
+}
+
+pub fn piece_from_char(c: char) -> Option<Piece> {
+    let color = if c.is_ascii_uppercase() {
+        Color::White
+    } else {
+        Color::Black
+    };
+
+    let piece_type = match c.to_ascii_lowercase() {
+        'p' => PieceType::Pawn,
+        'n' => PieceType::Knight,
+        'b' => PieceType::Bishop,
+        'r' => PieceType::Rook,
+        'q' => PieceType::Queen,
+        'k' => PieceType::King,
+        _ => return None,
+    };
+
+    Some(Piece::new(color, piece_type))
+}
+
+pub fn piece_to_char(piece: Piece) -> char {
+    let c = match piece.piece_type() {
+        PieceType::Pawn => 'p',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Rook => 'r',
+        PieceType::Queen => 'q',
+        PieceType::King => 'k',
+    };
+
+    match piece.color() {
+        Color::White => c.to_ascii_uppercase(),
+        Color::Black => c,
+    }
+}
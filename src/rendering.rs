@@ -112,8 +112,10 @@ pub fn load_piece_textures() -> FxHashMap<Piece, Texture2D> {
     ])
 }
 
+// hanging_pieces get a red square underneath, pieces the opponent can win material by taking
 pub fn render_board(
     possible_moves: u64,
+    hanging_pieces: u64,
     white_player: &Player,
     black_player: &Player,
     board_length: f32,
@@ -123,6 +125,7 @@ pub fn render_board(
     piece_textures: &FxHashMap<Piece, Texture2D>,
 ) {
     let possible_moves_coordinates = possible_moves.to_2d_coordinates();
+    let hanging_coordinates = hanging_pieces.to_2d_coordinates();
 
     for rank in 0..8 {
         for file in 0..8 {
@@ -143,6 +146,16 @@ pub fn render_board(
                 },
             );
 
+            if hanging_coordinates.contains(&(file, 7 - rank)) {
+                draw_rectangle(
+                    square_start_x,
+                    square_start_y,
+                    square_length,
+                    square_length,
+                    macroquad::color_u8!(0xe0, 0x30, 0x30, 0x90),
+                );
+            }
+
             if possible_moves_coordinates.contains(&(file, 7 - rank)) {
                 let radius = square_length * 0.2;
 
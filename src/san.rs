@@ -0,0 +1,171 @@
+use crate::{
+    player::PieceType,
+    position::{parse_square, square_name, Move, MoveKind, Position},
+};
+
+fn piece_type_from_san(c: char) -> Option<PieceType> {
+    match c {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+fn piece_type_to_san(piece_type: PieceType) -> &'static str {
+    match piece_type {
+        PieceType::Pawn => "",
+        PieceType::Knight => "N",
+        PieceType::Bishop => "B",
+        PieceType::Rook => "R",
+        PieceType::Queen => "Q",
+        PieceType::King => "K",
+    }
+}
+
+impl Position {
+    // standard algebraic notation as found in pgn files, annotations like + # ! ? are ignored
+    pub fn move_from_san(&self, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let legal_moves = self.legal_moves();
+
+        // some files use zeros for castling
+        match san {
+            "O-O" | "0-0" | "O-O-O" | "0-0-0" => {
+                let kingside = san.len() == 3;
+                return legal_moves.into_iter().find(|legal_move| {
+                    legal_move.kind == MoveKind::Castling
+                        && (legal_move.to > legal_move.from) == kingside
+                });
+            }
+            _ => {}
+        }
+
+        let mut chars: Vec<char> = san.chars().filter(|&c| c != 'x' && c != '=').collect();
+
+        let piece_type = match chars.first().copied().and_then(piece_type_from_san) {
+            Some(piece_type) => {
+                chars.remove(0);
+                piece_type
+            }
+            None => PieceType::Pawn,
+        };
+
+        let promotion = match chars.last().copied().and_then(piece_type_from_san) {
+            Some(promotion) if piece_type == PieceType::Pawn => {
+                chars.pop();
+                Some(promotion)
+            }
+            _ => None,
+        };
+
+        if chars.len() < 2 {
+            return None;
+        }
+
+        let destination: String = chars[chars.len() - 2..].iter().collect();
+        let to = parse_square(&destination)?;
+
+        // whatever is left before the destination narrows down which piece moves
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &c in &chars[..chars.len() - 2] {
+            match c {
+                'a'..='h' => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' => from_rank = Some(c as usize - '1' as usize),
+                _ => return None,
+            }
+        }
+
+        legal_moves.into_iter().find(|legal_move| {
+            legal_move.kind != MoveKind::Castling
+                && legal_move.to == to
+                && legal_move.promotion == promotion
+                && self
+                    .piece_at(legal_move.from)
+                    .is_some_and(|piece| piece.piece_type() == piece_type)
+                && from_file.is_none_or(|file| legal_move.from % 8 == file)
+                && from_rank.is_none_or(|rank| legal_move.from / 8 == rank)
+        })
+    }
+
+    pub fn move_to_san(&self, san_move: Move) -> String {
+        let mut san = if san_move.kind == MoveKind::Castling {
+            if san_move.to > san_move.from {
+                "O-O".to_owned()
+            } else {
+                "O-O-O".to_owned()
+            }
+        } else {
+            let piece_type = self
+                .piece_at(san_move.from)
+                .expect("no piece on the from square")
+                .piece_type();
+            let is_capture =
+                self.piece_at(san_move.to).is_some() || san_move.kind == MoveKind::EnPassant;
+            let from_name = square_name(san_move.from);
+
+            let mut san = piece_type_to_san(piece_type).to_owned();
+
+            if piece_type == PieceType::Pawn {
+                if is_capture {
+                    san.push_str(&from_name[..1]);
+                }
+            } else {
+                // only disambiguate as much as needed against other pieces of the same type
+                let others: Vec<Move> = self
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|other| {
+                        other.to == san_move.to
+                            && other.from != san_move.from
+                            && other.kind != MoveKind::Castling
+                            && self
+                                .piece_at(other.from)
+                                .is_some_and(|piece| piece.piece_type() == piece_type)
+                    })
+                    .collect();
+
+                if !others.is_empty() {
+                    if others
+                        .iter()
+                        .all(|other| other.from % 8 != san_move.from % 8)
+                    {
+                        san.push_str(&from_name[..1]);
+                    } else if others
+                        .iter()
+                        .all(|other| other.from / 8 != san_move.from / 8)
+                    {
+                        san.push_str(&from_name[1..]);
+                    } else {
+                        san.push_str(&from_name);
+                    }
+                }
+            }
+
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&square_name(san_move.to));
+
+            if let Some(promotion) = san_move.promotion {
+                san.push('=');
+                san.push_str(piece_type_to_san(promotion));
+            }
+
+            san
+        };
+
+        let mut after = self.clone();
+        after.make_move(san_move);
+        if after.is_checkmate() {
+            san.push('#');
+        } else if after.in_check() {
+            san.push('+');
+        }
+
+        san
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    player::{Color, Piece},
+    position::{Move, Position},
+};
+
+// scores drift towards this bound but never past it, so old lessons fade as new ones come in
+const MAX_HISTORY: i32 = 16384;
+const MAX_BONUS: i32 = 1600;
+
+// the moved piece and where it went, what continuation history and counter moves are keyed by
+pub type PieceTo = (Piece, usize);
+
+// the move played plies_ago moves back, None if the game has not gone on that long
+pub fn previous_move(position: &Position, plies_ago: usize) -> Option<PieceTo> {
+    let undo = position.history.iter().rev().nth(plies_ago - 1)?;
+    Some((undo.moved_piece, undo.played_move.to))
+}
+
+fn piece_to_index((piece, to): PieceTo) -> usize {
+    piece as usize * 64 + to
+}
+
+// the bonus for a quiet move that caused a cutoff, deeper cutoffs say more
+pub fn history_bonus(depth: i32) -> i32 {
+    (16 * depth * depth).min(MAX_BONUS)
+}
+
+fn apply_bonus(entry: &mut i32, bonus: i32) {
+    *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+}
+
+// how quiet moves have done before, learnt from cutoffs and kept from one search to the next
+pub struct History {
+    // [color][from][to]
+    butterfly: Vec<i32>,
+    // [previous piece][previous to][piece][to], how well a move does as a reply to another
+    continuation: Vec<i32>,
+    // [previous piece][previous to], the quiet move that last refuted it
+    counter_moves: Vec<Option<Move>>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            butterfly: vec![0; 2 * 64 * 64],
+            continuation: vec![0; 12 * 64 * 12 * 64],
+            counter_moves: vec![None; 12 * 64],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.butterfly.fill(0);
+        self.continuation.fill(0);
+        self.counter_moves.fill(None);
+    }
+
+    fn butterfly_index(color: Color, quiet: Move) -> usize {
+        color as usize * 64 * 64 + quiet.from * 64 + quiet.to
+    }
+
+    fn continuation_index(previous: PieceTo, current: PieceTo) -> usize {
+        piece_to_index(previous) * 12 * 64 + piece_to_index(current)
+    }
+
+    pub fn counter_move(&self, previous: Option<PieceTo>) -> Option<Move> {
+        self.counter_moves[piece_to_index(previous?)]
+    }
+
+    // previous holds the moves one and two plies back, the ones continuation history follows on from
+    pub fn quiet_score(
+        &self,
+        position: &Position,
+        quiet: Move,
+        previous: &[Option<PieceTo>],
+    ) -> i32 {
+        let current = (position.piece_at(quiet.from).unwrap(), quiet.to);
+        let continuation: i32 = previous
+            .iter()
+            .flatten()
+            .map(|&previous| self.continuation[Self::continuation_index(previous, current)])
+            .sum();
+
+        self.butterfly[Self::butterfly_index(position.side_to_move, quiet)] + continuation
+    }
+
+    // the quiet move that failed high gets a bonus, every quiet tried before it the same malus
+    pub fn update_quiets(
+        &mut self,
+        position: &Position,
+        best_move: Move,
+        tried: &[Move],
+        previous: &[Option<PieceTo>],
+        depth: i32,
+    ) {
+        let bonus = history_bonus(depth);
+
+        for &quiet in tried.iter().chain([&best_move]) {
+            let bonus = if quiet == best_move { bonus } else { -bonus };
+            let current = (position.piece_at(quiet.from).unwrap(), quiet.to);
+
+            apply_bonus(
+                &mut self.butterfly[Self::butterfly_index(position.side_to_move, quiet)],
+                bonus,
+            );
+            for &previous in previous.iter().flatten() {
+                apply_bonus(
+                    &mut self.continuation[Self::continuation_index(previous, current)],
+                    bonus,
+                );
+            }
+        }
+
+        if let Some(previous) = previous[0] {
+            self.counter_moves[piece_to_index(previous)] = Some(best_move);
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
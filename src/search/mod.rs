@@ -0,0 +1,716 @@
+pub mod history;
+pub mod move_picker;
+mod quiescence;
+pub mod selectivity;
+pub mod smp;
+pub mod time;
+pub mod tt;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{
+    eval::evaluate,
+    position::{Move, Position},
+    search::{
+        history::{previous_move, History, PieceTo},
+        move_picker::{is_quiet, MovePicker},
+        selectivity::{
+            late_move_pruning_threshold, late_move_reduction, Selectivity, FUTILITY_BASE_MARGIN,
+            FUTILITY_MARGIN, FUTILITY_MAX_DEPTH, LATE_MOVE_PRUNING_MAX_DEPTH,
+            LATE_MOVE_REDUCTION_MIN_DEPTH, NULL_MOVE_BASE_REDUCTION, NULL_MOVE_MIN_DEPTH,
+            NULL_MOVE_VERIFICATION_DEPTH, RAZORING_MARGIN, RAZORING_MAX_DEPTH,
+            REVERSE_FUTILITY_MARGIN, REVERSE_FUTILITY_MAX_DEPTH, SINGULAR_MARGIN,
+            SINGULAR_MIN_DEPTH,
+        },
+        time::{TimeControl, TimeManager},
+        tt::{Bound, TranspositionTable},
+    },
+};
+
+pub const MAX_PLY: usize = 128;
+pub const INFINITY: i32 = 32000;
+// mate found at ply p scores MATE_SCORE - p, so quicker mates score higher
+pub const MATE_SCORE: i32 = 31000;
+pub const MATE_BOUND: i32 = MATE_SCORE - MAX_PLY as i32;
+pub const MAX_DEPTH: u32 = MAX_PLY as u32 - 1;
+
+// how many nodes go by between looks at the stop flag, a power of two so it can be masked
+const STOP_CHECK_INTERVAL: u64 = 2048;
+
+// from this depth on each iteration first searches a narrow window around the last score
+const ASPIRATION_MIN_DEPTH: u32 = 4;
+const ASPIRATION_WINDOW: i32 = 25;
+
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_BOUND
+}
+
+// full moves until mate, negative when the side to move is the one getting mated
+pub fn mate_in(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+
+    let plies = MATE_SCORE - score.abs();
+    let moves = (plies + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
+// the score the way uci writes it, "cp 25" or "mate -3"
+pub fn format_score(score: i32) -> String {
+    match mate_in(score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", score),
+    }
+}
+
+// one root move with the best line following it, for multipv
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PvLine {
+    pub score: i32,
+    pub pv: Vec<Move>,
+}
+
+// what the deepest finished iteration came up with
+#[derive(Clone, Debug, Default)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    // centipawns from the point of view of the side to move
+    pub score: i32,
+    pub depth: u32,
+    pub pv: Vec<Move>,
+    // the best multipv lines, best first, the first one the same as the fields above
+    pub lines: Vec<PvLine>,
+    pub nodes: u64,
+}
+
+// when to stop searching, with nothing set the search goes on until the stop flag is set
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub move_time: Option<Duration>,
+    pub time_control: Option<TimeControl>,
+    // only the stop flag ends the search, the node, time and mate limits are ignored
+    pub infinite: bool,
+    // stop once a mate in this many moves or fewer is found
+    pub mate: Option<u32>,
+    // the root only looks at these moves, all of them when empty
+    pub search_moves: Vec<Move>,
+}
+
+pub struct Searcher {
+    pub tt: Arc<TranspositionTable>,
+    // set from anywhere to end the search, the last finished iteration is kept
+    pub stop: Arc<AtomicBool>,
+    pub nodes: u64,
+    stopped: bool,
+    root_depth: u32,
+    // pv_table[ply] is the best line found from ply onwards in the current node
+    pv_table: Vec<Vec<Move>>,
+    // two quiet moves per ply that caused cutoffs, likely to do it again in sibling nodes
+    killers: Vec<[Option<Move>; 2]>,
+    history: History,
+    // whether the move made at each ply was a null move, two in a row would prove nothing
+    null_moved: Vec<bool>,
+    // null moves stay off below this ply while a null move cutoff is being verified
+    null_move_min_ply: usize,
+    // the hash move left out of the search at each ply while checking for singular extension
+    excluded_moves: Vec<Option<Move>>,
+    // lazy smp helpers search this many plies deeper than the iteration they are on
+    depth_offset: u32,
+    // only there while searching on a clock or a fixed move time
+    time_manager: Option<TimeManager>,
+    node_limit: Option<u64>,
+    // the root moves being searched, all of them when empty
+    search_moves: Vec<Move>,
+    // root moves that already have a line of their own this iteration, for multipv
+    root_excluded: Vec<Move>,
+    pub selectivity: Selectivity,
+    // how many of the best root moves get searched and reported with their own line
+    pub multi_pv: usize,
+}
+
+impl Searcher {
+    pub fn new(tt: Arc<TranspositionTable>, stop: Arc<AtomicBool>) -> Self {
+        Searcher {
+            tt,
+            stop,
+            nodes: 0,
+            stopped: false,
+            root_depth: 0,
+            pv_table: vec![Vec::new(); MAX_PLY + 1],
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            history: History::new(),
+            null_moved: vec![false; MAX_PLY + 1],
+            null_move_min_ply: 0,
+            excluded_moves: vec![None; MAX_PLY + 1],
+            depth_offset: 0,
+            time_manager: None,
+            node_limit: None,
+            search_moves: Vec::new(),
+            root_excluded: Vec::new(),
+            selectivity: Selectivity::default(),
+            multi_pv: 1,
+        }
+    }
+
+    // forgets everything learnt, the table included, so a new game starts from scratch
+    pub fn new_game(&mut self) {
+        self.tt.clear();
+        self.history.clear();
+    }
+
+    // iterative deepening until one of the limits is hit, report is called after every finished
+    // iteration
+    pub fn search(
+        &mut self,
+        position: &mut Position,
+        limits: &SearchLimits,
+        report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.tt.new_search();
+        self.iterative_deepening(position, limits, report)
+    }
+
+    // the search itself, without starting a new search in the table, which threads searching
+    // together must only do once between them
+    fn iterative_deepening(
+        &mut self,
+        position: &mut Position,
+        limits: &SearchLimits,
+        mut report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.nodes = 0;
+        self.stopped = false;
+        self.killers.fill([None; 2]);
+        self.search_moves = limits.search_moves.clone();
+
+        if limits.infinite {
+            self.time_manager = None;
+            self.node_limit = None;
+        } else {
+            self.time_manager = limits.move_time.map(TimeManager::fixed).or_else(|| {
+                limits
+                    .time_control
+                    .map(|time_control| TimeManager::new(&time_control, position))
+            });
+            self.node_limit = limits.nodes;
+        }
+        let mate_limit = limits.mate.filter(|_| !limits.infinite);
+
+        let mut result = SearchResult::default();
+        let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+
+        for iteration in 1..=max_depth {
+            let depth = (iteration + self.depth_offset).min(max_depth);
+            self.root_depth = depth;
+
+            // each line searches the root again without the best moves of the lines before it
+            let mut lines: Vec<PvLine> = Vec::new();
+            self.root_excluded.clear();
+            while lines.len() < self.multi_pv.max(1) {
+                let previous_score = result
+                    .lines
+                    .get(lines.len())
+                    .map_or(result.score, |line| line.score);
+                let score = self.aspiration_search(position, depth, previous_score);
+                if self.stopped {
+                    break;
+                }
+
+                let Some(&best_move) = self.pv_table[0].first() else {
+                    // out of root moves, the first line still has to tell mate from stalemate
+                    if lines.is_empty() {
+                        lines.push(PvLine {
+                            score,
+                            pv: Vec::new(),
+                        });
+                    }
+                    break;
+                };
+                lines.push(PvLine {
+                    score,
+                    pv: self.pv_table[0].clone(),
+                });
+                self.root_excluded.push(best_move);
+            }
+            self.root_excluded.clear();
+
+            // a partly searched iteration can not be trusted
+            if self.stopped {
+                break;
+            }
+
+            // a later line can still come out ahead of an earlier one as the table fills in
+            lines.sort_by_key(|line| -line.score);
+            let score = lines[0].score;
+            result = SearchResult {
+                best_move: lines[0].pv.first().copied(),
+                score,
+                depth,
+                pv: lines[0].pv.clone(),
+                lines,
+                nodes: self.nodes,
+            };
+            report(&result);
+
+            if result.best_move.is_none() || depth == max_depth {
+                break;
+            }
+
+            let mate_found = mate_limit.is_some_and(|mate_limit| {
+                mate_in(score).is_some_and(|moves| moves > 0 && moves as u32 <= mate_limit)
+            });
+            if mate_found {
+                break;
+            }
+
+            if let Some(time_manager) = &mut self.time_manager {
+                if !time_manager.should_continue(&result) {
+                    break;
+                }
+            }
+        }
+
+        result
+    }
+
+    // the score rarely moves much from one iteration to the next, so a narrow window around the
+    // last one cuts off far more, widening it every time the real score falls outside
+    fn aspiration_search(
+        &mut self,
+        position: &mut Position,
+        depth: u32,
+        previous_score: i32,
+    ) -> i32 {
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) =
+            if depth >= ASPIRATION_MIN_DEPTH && !is_mate_score(previous_score) {
+                (previous_score - delta, previous_score + delta)
+            } else {
+                (-INFINITY, INFINITY)
+            };
+
+        loop {
+            let score = self.negamax(position, depth as i32, 0, alpha, beta);
+            if self.stopped {
+                return score;
+            }
+
+            if score <= alpha {
+                // pull beta down too, the line that set it is no longer the best one
+                beta = (alpha + beta) / 2;
+                alpha = (score - delta).max(-INFINITY);
+            } else if score >= beta {
+                beta = (score + delta).min(INFINITY);
+            } else {
+                return score;
+            }
+
+            delta += delta / 2;
+        }
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.nodes & (STOP_CHECK_INTERVAL - 1) == 0
+            && (self.stop.load(Ordering::Relaxed)
+                || self
+                    .time_manager
+                    .as_ref()
+                    .is_some_and(|time_manager| time_manager.hard_limit_reached()))
+        {
+            self.stopped = true;
+        }
+
+        // counted exactly, a node limit is often used to make searches repeatable
+        if self
+            .node_limit
+            .is_some_and(|node_limit| self.nodes >= node_limit)
+        {
+            self.stopped = true;
+        }
+
+        self.stopped
+    }
+
+    // the moves one and two plies back, a null move in between breaks the chain
+    fn previous_moves(&self, position: &Position, ply: usize) -> [Option<PieceTo>; 2] {
+        let after_null =
+            (1..=2).any(|plies_ago| ply >= plies_ago && self.null_moved[ply - plies_ago]);
+        if after_null {
+            return [None; 2];
+        }
+
+        [previous_move(position, 1), previous_move(position, 2)]
+    }
+
+    fn negamax(
+        &mut self,
+        position: &mut Position,
+        depth: i32,
+        ply: usize,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> i32 {
+        if depth <= 0 {
+            return self.quiescence(position, ply, alpha, beta);
+        }
+
+        self.pv_table[ply].clear();
+        self.nodes += 1;
+
+        // the first iteration runs to the end no matter what so there is always a move to play
+        if self.root_depth > 1 && self.should_stop() {
+            return 0;
+        }
+
+        if ply > 0 && (position.halfmove_clock >= 100 || position.is_repetition()) {
+            return 0;
+        }
+
+        if ply >= MAX_PLY {
+            return evaluate(position);
+        }
+
+        // mate distance pruning, no line from here can beat a mate already found closer to the root
+        if ply > 0 {
+            alpha = alpha.max(-MATE_SCORE + ply as i32);
+            beta = beta.min(MATE_SCORE - ply as i32 - 1);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
+        // set while checking whether the hash move is singular, that search leaves it out
+        let excluded_move = self.excluded_moves[ply];
+
+        let tt_entry = match excluded_move {
+            Some(_) => None,
+            None => self.tt.probe(position.zobrist_key, ply),
+        };
+        if let Some(entry) = tt_entry {
+            // the root always searches so it has a move and a line to give back
+            if ply > 0
+                && entry.depth as i32 >= depth
+                && match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                }
+            {
+                return entry.score;
+            }
+        }
+        let tt_move = tt_entry.and_then(|entry| entry.best_move);
+
+        let in_check = position.in_check();
+        let static_eval = if in_check {
+            -INFINITY
+        } else {
+            evaluate(position)
+        };
+        let selectivity = self.selectivity;
+        let can_prune = ply > 0 && !in_check && excluded_move.is_none();
+
+        // so far ahead that even a margin per ply to go would not bring it back under beta
+        if can_prune
+            && selectivity.reverse_futility
+            && depth <= REVERSE_FUTILITY_MAX_DEPTH
+            && !is_mate_score(beta)
+            && static_eval - REVERSE_FUTILITY_MARGIN * depth >= beta
+        {
+            return static_eval;
+        }
+
+        // so far behind that only captures could help, if they do not either give up here
+        if can_prune
+            && selectivity.razoring
+            && depth <= RAZORING_MAX_DEPTH
+            && static_eval + RAZORING_MARGIN * depth < alpha
+        {
+            let score = self.quiescence(position, ply, alpha, beta);
+            if score < alpha {
+                return score;
+            }
+        }
+
+        // if passing the turn still keeps us above beta a real move surely would too,
+        // but not when only pawns are left as zugzwang is common there
+        if can_prune
+            && selectivity.null_move
+            && depth >= NULL_MOVE_MIN_DEPTH
+            && ply >= self.null_move_min_ply
+            && !self.null_moved[ply - 1]
+            && static_eval >= beta
+            && !is_mate_score(beta)
+            && has_non_pawn_material(position)
+        {
+            let reduction = NULL_MOVE_BASE_REDUCTION + depth / 4;
+
+            let undo = position.make_null_move();
+            self.null_moved[ply] = true;
+            let score = -self.negamax(position, depth - 1 - reduction, ply + 1, -beta, -beta + 1);
+            self.null_moved[ply] = false;
+            position.unmake_null_move(undo);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score >= beta {
+                // a mate found after passing is not a real one
+                let score = if is_mate_score(score) { beta } else { score };
+
+                if depth < NULL_MOVE_VERIFICATION_DEPTH {
+                    return score;
+                }
+
+                // deep down the cutoff is checked with null moves off for the next few plies
+                // a verification inside another one keeps the outer limit if it reaches further
+                let saved_min_ply = self.null_move_min_ply;
+                self.null_move_min_ply =
+                    saved_min_ply.max(ply + (3 * (depth - reduction) / 4) as usize);
+                let verified = self.negamax(position, depth - 1 - reduction, ply, beta - 1, beta);
+                self.null_move_min_ply = saved_min_ply;
+
+                if verified >= beta {
+                    return score;
+                }
+            }
+        }
+
+        // a hash move that failed high deep enough might be the only good move here, if every other
+        // move falls well short of it it gets searched a ply deeper
+        let singular_entry = tt_entry.filter(|entry| {
+            selectivity.singular_extensions
+                && ply > 0
+                && excluded_move.is_none()
+                && depth >= SINGULAR_MIN_DEPTH
+                && entry.best_move.is_some()
+                && entry.depth as i32 >= depth - 3
+                && entry.bound != Bound::Upper
+                && !is_mate_score(entry.score)
+        });
+
+        let previous = self.previous_moves(position, ply);
+        let mut picker = MovePicker::new(tt_move, self.killers[ply], previous);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        let mut legal_moves = 0;
+        let mut quiets_tried = Vec::new();
+
+        while let Some(candidate) = picker.next(position, &self.history) {
+            if Some(candidate) == excluded_move || !position.is_legal(candidate) {
+                continue;
+            }
+            if ply == 0
+                && (self.root_excluded.contains(&candidate)
+                    || !self.search_moves.is_empty() && !self.search_moves.contains(&candidate))
+            {
+                continue;
+            }
+            legal_moves += 1;
+            let quiet = is_quiet(position, candidate);
+            let pruning_allowed = can_prune && quiet && best_score > -MATE_BOUND;
+
+            // enough quiet moves tried this close to the horizon, the rest are ordered too badly to matter
+            if pruning_allowed
+                && selectivity.late_move_pruning
+                && depth <= LATE_MOVE_PRUNING_MAX_DEPTH
+                && quiets_tried.len() >= late_move_pruning_threshold(depth)
+            {
+                continue;
+            }
+
+            let mut extension = 0;
+            if let Some(entry) = singular_entry.filter(|entry| entry.best_move == Some(candidate)) {
+                let singular_beta = entry.score - SINGULAR_MARGIN * depth;
+
+                self.excluded_moves[ply] = Some(candidate);
+                let score = self.negamax(
+                    position,
+                    (depth - 1) / 2,
+                    ply,
+                    singular_beta - 1,
+                    singular_beta,
+                );
+                self.excluded_moves[ply] = None;
+
+                if self.stopped {
+                    return 0;
+                }
+
+                if score < singular_beta {
+                    extension = 1;
+                } else if singular_beta >= beta {
+                    // even without the hash move something beats beta, more than one move fails high
+                    return singular_beta;
+                }
+            }
+
+            position.make_move(candidate);
+            let gives_check = position.in_check();
+
+            // a quiet move that can not lift the eval anywhere near alpha
+            if pruning_allowed
+                && selectivity.futility
+                && !gives_check
+                && depth <= FUTILITY_MAX_DEPTH
+                && static_eval + FUTILITY_BASE_MARGIN + FUTILITY_MARGIN * depth <= alpha
+            {
+                position.unmake_move();
+                quiets_tried.push(candidate);
+                continue;
+            }
+
+            // extensions stop once the line is twice as long as the iteration, so checks can not go on forever
+            if selectivity.check_extensions && gives_check && ply < 2 * self.root_depth as usize {
+                extension = extension.max(1);
+            }
+            let new_depth = depth - 1 + extension;
+
+            // late quiet moves are searched shallower first, and at full depth again only if they
+            // turn out better than expected
+            let reduction = if selectivity.late_move_reductions
+                && depth >= LATE_MOVE_REDUCTION_MIN_DEPTH
+                && legal_moves > 1
+                && quiet
+                && !in_check
+                && !gives_check
+            {
+                let mut reduction = late_move_reduction(depth, legal_moves);
+                if self.killers[ply].contains(&Some(candidate)) {
+                    reduction -= 1;
+                }
+                reduction.clamp(0, new_depth - 1)
+            } else {
+                0
+            };
+
+            // principal variation search, only the first move gets the full window, every other
+            // one just has to be shown worse with a null window unless it turns out better
+            let score = if legal_moves == 1 {
+                -self.negamax(position, new_depth, ply + 1, -beta, -alpha)
+            } else {
+                let mut score =
+                    -self.negamax(position, new_depth - reduction, ply + 1, -alpha - 1, -alpha);
+                if score > alpha && reduction > 0 {
+                    score = -self.negamax(position, new_depth, ply + 1, -alpha - 1, -alpha);
+                }
+                if score > alpha && score < beta {
+                    score = -self.negamax(position, new_depth, ply + 1, -beta, -alpha);
+                }
+                score
+            };
+            position.unmake_move();
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+
+                if score > alpha {
+                    alpha = score;
+                    best_move = Some(candidate);
+
+                    // this move followed by the best line found below it
+                    let (current, below) = self.pv_table.split_at_mut(ply + 1);
+                    current[ply].clear();
+                    current[ply].push(candidate);
+                    current[ply].extend_from_slice(&below[0]);
+
+                    if alpha >= beta {
+                        if quiet {
+                            self.store_killer(ply, candidate);
+                            self.history.update_quiets(
+                                position,
+                                candidate,
+                                &quiets_tried,
+                                &previous,
+                                depth,
+                            );
+                        }
+                        break;
+                    }
+                }
+            }
+
+            if quiet {
+                quiets_tried.push(candidate);
+            }
+        }
+
+        if legal_moves == 0 {
+            return if excluded_move.is_some() {
+                // the hash move was the only move, which is as singular as it gets
+                alpha
+            } else if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                0
+            };
+        }
+
+        // a root limited to some of its moves may not have found the real best one
+        let restricted_root =
+            ply == 0 && (!self.search_moves.is_empty() || !self.root_excluded.is_empty());
+        if excluded_move.is_none() && !restricted_root {
+            let bound = if best_score >= beta {
+                Bound::Lower
+            } else if alpha > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            self.tt.store(
+                position.zobrist_key,
+                best_move,
+                best_score,
+                depth as u32,
+                bound,
+                ply,
+            );
+        }
+
+        best_score
+    }
+
+    fn store_killer(&mut self, ply: usize, killer: Move) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(killer) {
+            killers[1] = killers[0];
+            killers[0] = Some(killer);
+        }
+    }
+}
+
+// null moves are only safe with pieces other than pawns around to move
+fn has_non_pawn_material(position: &Position) -> bool {
+    let player = position.player(position.side_to_move);
+    player.knight_bitboard | player.bishop_bitboard | player.rook_bitboard | player.queen_bitboard
+        != 0
+}
+
+// for when nothing but the limits needs to stop the search
+pub fn search(
+    position: &mut Position,
+    limits: &SearchLimits,
+    report: impl FnMut(&SearchResult),
+) -> SearchResult {
+    Searcher::new(
+        Arc::new(TranspositionTable::default()),
+        Arc::new(AtomicBool::new(false)),
+    )
+    .search(position, limits, report)
+}
@@ -0,0 +1,212 @@
+use crate::{
+    player::PieceType,
+    position::{Move, MoveKind, Position},
+    search::history::{History, PieceTo},
+    see::{see_ge, see_value},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    TtMove,
+    GenerateCaptures,
+    GoodCaptures,
+    FirstKiller,
+    SecondKiller,
+    CounterMove,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+// most valuable victim first, least valuable attacker breaking ties
+pub fn mvv_lva(position: &Position, capture: Move) -> i32 {
+    let victim = match capture.kind {
+        MoveKind::EnPassant => PieceType::Pawn,
+        _ => position
+            .piece_at(capture.to)
+            .map_or(PieceType::Pawn, |piece| piece.piece_type()),
+    };
+    let attacker = position.piece_at(capture.from).unwrap().piece_type();
+
+    see_value(victim) * 10 - see_value(attacker) / 10
+}
+
+// promotions sit with the captures, they change the material just as much
+pub fn is_quiet(position: &Position, candidate: Move) -> bool {
+    !position.is_capture(candidate) && candidate.promotion.is_none()
+}
+
+// hands out pseudo legal moves best first, one stage at a time, so a cutoff from the hash move
+// or a capture means the quiet moves never get generated at all
+pub struct MovePicker {
+    stage: Stage,
+    tt_move: Option<Move>,
+    killers: [Option<Move>; 2],
+    counter_move: Option<Move>,
+    previous: [Option<PieceTo>; 2],
+    // the current stages moves with their scores, picked from best to worst
+    moves: Vec<(Move, i32)>,
+    bad_captures: Vec<Move>,
+    captures_only: bool,
+}
+
+impl MovePicker {
+    // previous holds the moves one and two plies back, for counter moves and continuation history
+    pub fn new(
+        tt_move: Option<Move>,
+        killers: [Option<Move>; 2],
+        previous: [Option<PieceTo>; 2],
+    ) -> Self {
+        MovePicker {
+            stage: Stage::TtMove,
+            tt_move,
+            killers,
+            counter_move: None,
+            previous,
+            moves: Vec::new(),
+            bad_captures: Vec::new(),
+            captures_only: false,
+        }
+    }
+
+    // captures and promotions alone, for the quiescence search
+    pub fn captures(tt_move: Option<Move>) -> Self {
+        MovePicker {
+            captures_only: true,
+            ..Self::new(tt_move, [None; 2], [None; 2])
+        }
+    }
+
+    // a quiet move handed out already in an earlier stage
+    fn already_picked(&self, quiet: Move) -> bool {
+        Some(quiet) == self.tt_move
+            || Some(quiet) == self.counter_move
+            || self.killers.contains(&Some(quiet))
+    }
+
+    // the best move left in the current stage, a selection sort done one step at a time
+    fn pick_best(&mut self) -> Option<Move> {
+        let best_index = self
+            .moves
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (_, score))| *score)?
+            .0;
+
+        Some(self.moves.swap_remove(best_index).0)
+    }
+
+    // a killer or counter move, as long as it is a different quiet move that fits this position
+    fn refutation(&self, position: &Position, candidate: Option<Move>) -> Option<Move> {
+        let candidate = candidate?;
+
+        (Some(candidate) != self.tt_move
+            && is_quiet(position, candidate)
+            && position.is_pseudo_legal(candidate))
+        .then_some(candidate)
+    }
+
+    pub fn next(&mut self, position: &Position, history: &History) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::TtMove => {
+                    self.stage = Stage::GenerateCaptures;
+
+                    if let Some(tt_move) = self.tt_move {
+                        if position.is_pseudo_legal(tt_move)
+                            && (!self.captures_only || !is_quiet(position, tt_move))
+                        {
+                            return Some(tt_move);
+                        }
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    let mut captures = Vec::new();
+                    position.generate_captures(&mut captures);
+
+                    self.moves = captures
+                        .into_iter()
+                        .filter(|&capture| Some(capture) != self.tt_move)
+                        .map(|capture| {
+                            let score = if position.is_capture(capture) {
+                                mvv_lva(position, capture)
+                            } else {
+                                see_value(capture.promotion.unwrap())
+                            };
+                            (capture, score)
+                        })
+                        .collect();
+                    self.stage = Stage::GoodCaptures;
+                }
+                Stage::GoodCaptures => match self.pick_best() {
+                    // losing captures and underpromotions wait until after the quiet moves
+                    Some(capture)
+                        if !see_ge(position, capture, 0)
+                            || matches!(
+                                capture.promotion,
+                                Some(promotion) if promotion != PieceType::Queen
+                            ) =>
+                    {
+                        self.bad_captures.push(capture);
+                    }
+                    Some(capture) => return Some(capture),
+                    None => {
+                        self.stage = if self.captures_only {
+                            Stage::BadCaptures
+                        } else {
+                            Stage::FirstKiller
+                        };
+                    }
+                },
+                Stage::FirstKiller => {
+                    self.stage = Stage::SecondKiller;
+                    if let Some(killer) = self.refutation(position, self.killers[0]) {
+                        return Some(killer);
+                    }
+                }
+                Stage::SecondKiller => {
+                    self.stage = Stage::CounterMove;
+                    if let Some(killer) = self.refutation(position, self.killers[1]) {
+                        if self.killers[0] != Some(killer) {
+                            return Some(killer);
+                        }
+                    }
+                }
+                Stage::CounterMove => {
+                    self.stage = Stage::GenerateQuiets;
+                    self.counter_move = history.counter_move(self.previous[0]);
+                    if let Some(counter_move) = self.refutation(position, self.counter_move) {
+                        if !self.killers.contains(&Some(counter_move)) {
+                            return Some(counter_move);
+                        }
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    let mut quiets = Vec::new();
+                    position.generate_quiets(&mut quiets);
+
+                    self.moves = quiets
+                        .into_iter()
+                        .filter(|&quiet| !self.already_picked(quiet))
+                        .map(|quiet| (quiet, history.quiet_score(position, quiet, &self.previous)))
+                        .collect();
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => match self.pick_best() {
+                    Some(quiet) => return Some(quiet),
+                    None => self.stage = Stage::BadCaptures,
+                },
+                Stage::BadCaptures => {
+                    if self.bad_captures.is_empty() {
+                        self.stage = Stage::Done;
+                    } else {
+                        // they went in best first
+                        return Some(self.bad_captures.remove(0));
+                    }
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+}
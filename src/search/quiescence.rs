@@ -0,0 +1,119 @@
+use crate::{
+    eval::evaluate,
+    player::PieceType,
+    position::{MoveKind, Position},
+    search::{
+        history::previous_move, move_picker::MovePicker, Searcher, INFINITY, MATE_SCORE, MAX_PLY,
+    },
+    see::{see_ge, see_value},
+};
+
+// even winning the captured piece outright has to get within this of alpha to be worth a look
+const DELTA_MARGIN: i32 = 200;
+
+impl Searcher {
+    // only captures and queen promotions past the horizon, or every evasion when in check,
+    // so the static eval is never taken in the middle of an exchange
+    pub(super) fn quiescence(
+        &mut self,
+        position: &mut Position,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.pv_table[ply].clear();
+        self.nodes += 1;
+
+        if self.root_depth > 1 && self.should_stop() {
+            return 0;
+        }
+
+        if position.halfmove_clock >= 100 || position.is_repetition() {
+            return 0;
+        }
+
+        if ply >= MAX_PLY {
+            return evaluate(position);
+        }
+
+        let in_check = position.in_check();
+
+        // standing pat is not an option in check, every evasion is searched instead
+        let (stand_pat, mut picker) = if in_check {
+            let previous = [previous_move(position, 1), previous_move(position, 2)];
+            (-INFINITY, MovePicker::new(None, [None; 2], previous))
+        } else {
+            let stand_pat = evaluate(position);
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+
+            (stand_pat, MovePicker::captures(None))
+        };
+
+        let mut best_score = stand_pat;
+        let mut legal_moves = 0;
+
+        while let Some(candidate) = picker.next(position, &self.history) {
+            if !position.is_legal(candidate) {
+                continue;
+            }
+            legal_moves += 1;
+
+            if !in_check {
+                if matches!(candidate.promotion, Some(promotion) if promotion != PieceType::Queen) {
+                    continue;
+                }
+
+                // captures that lose material once the exchange plays out
+                if !see_ge(position, candidate, 0) {
+                    continue;
+                }
+            }
+
+            let captured_value = match candidate.kind {
+                MoveKind::EnPassant => see_value(PieceType::Pawn),
+                _ => position
+                    .piece_at(candidate.to)
+                    .map_or(0, |piece| see_value(piece.piece_type())),
+            };
+            // delta pruning, promotions are left alone as they gain more than the capture
+            let hopeless = !in_check
+                && candidate.promotion.is_none()
+                && stand_pat + captured_value + DELTA_MARGIN <= alpha;
+
+            position.make_move(candidate);
+
+            // unless it gives check, a capture that can not win the game back might still mate
+            if hopeless && !position.in_check() {
+                position.unmake_move();
+                continue;
+            }
+
+            let score = -self.quiescence(position, ply + 1, -beta, -alpha);
+            position.unmake_move();
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+
+                if score > alpha {
+                    alpha = score;
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if in_check && legal_moves == 0 {
+            return -MATE_SCORE + ply as i32;
+        }
+
+        best_score
+    }
+}
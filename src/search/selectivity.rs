@@ -0,0 +1,125 @@
+use std::sync::OnceLock;
+
+// null move: the side to move passes and the rest is searched this much shallower
+pub const NULL_MOVE_MIN_DEPTH: i32 = 3;
+pub const NULL_MOVE_BASE_REDUCTION: i32 = 3;
+// from here on a null move cutoff is checked again without null moves, in case of zugzwang
+pub const NULL_MOVE_VERIFICATION_DEPTH: i32 = 12;
+
+pub const REVERSE_FUTILITY_MAX_DEPTH: i32 = 8;
+pub const REVERSE_FUTILITY_MARGIN: i32 = 80;
+
+pub const RAZORING_MAX_DEPTH: i32 = 3;
+pub const RAZORING_MARGIN: i32 = 250;
+
+pub const FUTILITY_MAX_DEPTH: i32 = 6;
+pub const FUTILITY_BASE_MARGIN: i32 = 100;
+pub const FUTILITY_MARGIN: i32 = 100;
+
+pub const LATE_MOVE_PRUNING_MAX_DEPTH: i32 = 8;
+
+pub const LATE_MOVE_REDUCTION_MIN_DEPTH: i32 = 3;
+
+pub const SINGULAR_MIN_DEPTH: i32 = 8;
+// the hash move counts as singular when every other move is this much per depth worse
+pub const SINGULAR_MARGIN: i32 = 2;
+
+// every selective technique can be turned off on its own, to see what each one is worth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selectivity {
+    pub null_move: bool,
+    pub late_move_reductions: bool,
+    pub futility: bool,
+    pub reverse_futility: bool,
+    pub razoring: bool,
+    pub late_move_pruning: bool,
+    pub check_extensions: bool,
+    pub singular_extensions: bool,
+}
+
+impl Selectivity {
+    pub const NAMES: [&'static str; 8] = [
+        "null-move",
+        "lmr",
+        "futility",
+        "reverse-futility",
+        "razoring",
+        "lmp",
+        "check-extensions",
+        "singular-extensions",
+    ];
+
+    pub const ALL: Selectivity = Selectivity {
+        null_move: true,
+        late_move_reductions: true,
+        futility: true,
+        reverse_futility: true,
+        razoring: true,
+        late_move_pruning: true,
+        check_extensions: true,
+        singular_extensions: true,
+    };
+
+    pub const NONE: Selectivity = Selectivity {
+        null_move: false,
+        late_move_reductions: false,
+        futility: false,
+        reverse_futility: false,
+        razoring: false,
+        late_move_pruning: false,
+        check_extensions: false,
+        singular_extensions: false,
+    };
+
+    // name is one of NAMES
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let technique = match name {
+            "null-move" => &mut self.null_move,
+            "lmr" => &mut self.late_move_reductions,
+            "futility" => &mut self.futility,
+            "reverse-futility" => &mut self.reverse_futility,
+            "razoring" => &mut self.razoring,
+            "lmp" => &mut self.late_move_pruning,
+            "check-extensions" => &mut self.check_extensions,
+            "singular-extensions" => &mut self.singular_extensions,
+            _ => {
+                return Err(format!(
+                    "unknown technique {}, expected one of {}",
+                    name,
+                    Self::NAMES.join(", ")
+                ))
+            }
+        };
+
+        *technique = enabled;
+        Ok(())
+    }
+}
+
+impl Default for Selectivity {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+// how many plies less a late quiet move is searched, grows with both depth and how late the move is
+pub fn late_move_reduction(depth: i32, move_number: usize) -> i32 {
+    static REDUCTIONS: OnceLock<[[i32; 64]; 64]> = OnceLock::new();
+
+    let reductions = REDUCTIONS.get_or_init(|| {
+        let mut reductions = [[0; 64]; 64];
+        for (depth, row) in reductions.iter_mut().enumerate().skip(1) {
+            for (move_number, reduction) in row.iter_mut().enumerate().skip(1) {
+                *reduction = (0.75 + (depth as f64).ln() * (move_number as f64).ln() / 2.25) as i32;
+            }
+        }
+        reductions
+    });
+
+    reductions[depth.clamp(0, 63) as usize][move_number.min(63)]
+}
+
+// quiet moves tried at this depth before the rest are not worth a look
+pub fn late_move_pruning_threshold(depth: i32) -> usize {
+    (3 + depth * depth) as usize
+}
@@ -0,0 +1,131 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use crate::{
+    position::Position,
+    search::{
+        selectivity::Selectivity, tt::TranspositionTable, SearchLimits, SearchResult, Searcher,
+    },
+};
+
+// lazy smp, every thread runs its own iterative deepening on the same position and all they share
+// is the transposition table, every other helper going a ply deeper so they spread out over the
+// tree instead of all searching the same nodes
+pub struct ThreadPool {
+    pub tt: Arc<TranspositionTable>,
+    // set from anywhere to end the search, only the main thread looks at it
+    pub stop: Arc<AtomicBool>,
+    pub selectivity: Selectivity,
+    pub multi_pv: usize,
+    main: Searcher,
+    helpers: Vec<Searcher>,
+    // set once the main thread is done so the helpers finish along with it
+    helpers_stop: Arc<AtomicBool>,
+}
+
+impl ThreadPool {
+    pub fn new(threads: usize, tt: Arc<TranspositionTable>, stop: Arc<AtomicBool>) -> Self {
+        let mut pool = ThreadPool {
+            main: Searcher::new(tt.clone(), stop.clone()),
+            helpers: Vec::new(),
+            helpers_stop: Arc::new(AtomicBool::new(false)),
+            tt,
+            stop,
+            selectivity: Selectivity::default(),
+            multi_pv: 1,
+        };
+        pool.set_threads(threads);
+        pool
+    }
+
+    // the main thread counts as one of them
+    pub fn set_threads(&mut self, threads: usize) {
+        let helper_count = threads.max(1) - 1;
+
+        self.helpers.truncate(helper_count);
+        while self.helpers.len() < helper_count {
+            let mut helper = Searcher::new(self.tt.clone(), self.helpers_stop.clone());
+            helper.depth_offset = (self.helpers.len() as u32 + 1) % 2;
+            self.helpers.push(helper);
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.helpers.len() + 1
+    }
+
+    // nodes each thread searched last time, the main thread first
+    pub fn thread_nodes(&self) -> Vec<u64> {
+        std::iter::once(&self.main)
+            .chain(&self.helpers)
+            .map(|searcher| searcher.nodes)
+            .collect()
+    }
+
+    pub fn new_game(&mut self) {
+        self.main.new_game();
+        for helper in &mut self.helpers {
+            helper.history.clear();
+        }
+    }
+
+    // only the main thread reports its iterations and keeps to the limits, the helpers stop
+    // whenever it does, the result is the deepest any thread finished
+    pub fn search(
+        &mut self,
+        position: &Position,
+        limits: &SearchLimits,
+        report: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.tt.new_search();
+        self.helpers_stop.store(false, Ordering::Relaxed);
+
+        for searcher in std::iter::once(&mut self.main).chain(&mut self.helpers) {
+            searcher.selectivity = self.selectivity;
+            searcher.multi_pv = self.multi_pv;
+        }
+
+        let helper_limits = SearchLimits {
+            depth: limits.depth,
+            search_moves: limits.search_moves.clone(),
+            ..SearchLimits::default()
+        };
+
+        let (main, helpers, helpers_stop) = (&mut self.main, &mut self.helpers, &self.helpers_stop);
+        let mut best = thread::scope(|scope| {
+            let handles: Vec<_> = helpers
+                .iter_mut()
+                .map(|helper| {
+                    let mut position = position.clone();
+                    let helper_limits = &helper_limits;
+                    scope.spawn(move || {
+                        helper.iterative_deepening(&mut position, helper_limits, |_| {})
+                    })
+                })
+                .collect();
+
+            let mut best = main.iterative_deepening(&mut position.clone(), limits, report);
+            helpers_stop.store(true, Ordering::Relaxed);
+
+            for handle in handles {
+                let result = handle.join().unwrap();
+                if result.best_move.is_some()
+                    && (result.depth > best.depth
+                        || result.depth == best.depth && result.score > best.score)
+                {
+                    best = result;
+                }
+            }
+
+            best
+        });
+
+        best.nodes = self.thread_nodes().iter().sum();
+        best
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    player::Color,
+    position::{Move, Position},
+    search::SearchResult,
+};
+
+// kept back on every move for the time it takes the move to reach the clock
+pub const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+// how many more moves the game is guessed to last when the clock does not say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// a search that keeps asking for more time can take this many times its share
+const HARD_LIMIT_FACTOR: u32 = 4;
+// the most of what is left on the clock any one move may use up
+pub const MAX_TIME_USAGE: f64 = 0.8;
+
+// the clocks as the go command gives them, for both sides
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeControl {
+    pub white_time: Duration,
+    pub black_time: Duration,
+    pub white_increment: Duration,
+    pub black_increment: Duration,
+    // moves until the next time control, None for sudden death
+    pub moves_to_go: Option<u32>,
+}
+
+// turns the clock into how long to think about this one move
+pub struct TimeManager {
+    start: Instant,
+    // no new iteration is started past this, stretched or shrunk by how the search is going
+    soft_limit: Duration,
+    // the search is cut off in the middle of an iteration past this
+    hard_limit: Duration,
+    // only one legal move, no point thinking about it
+    forced_move: bool,
+    // a fixed move time is used up whatever the search looks like
+    fixed: bool,
+    previous_best_move: Option<Move>,
+    // iterations in a row the best move has stayed the same
+    stability: u32,
+    previous_score: Option<i32>,
+    // how far the score fell in the last iteration, up to 100
+    score_drop: i32,
+}
+
+impl TimeManager {
+    // the clock starts running here, so make it as soon as the go command comes in
+    pub fn new(control: &TimeControl, position: &Position) -> Self {
+        let (time, increment) = match position.side_to_move {
+            Color::White => (control.white_time, control.white_increment),
+            Color::Black => (control.black_time, control.black_increment),
+        };
+
+        let available = time.saturating_sub(MOVE_OVERHEAD);
+        let moves_to_go = control.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let max_time = available.mul_f64(MAX_TIME_USAGE);
+        // an even share of the time left, and most of the increment as it comes back anyway
+        let planned = available / moves_to_go + increment * 3 / 4;
+
+        TimeManager {
+            start: Instant::now(),
+            soft_limit: planned.min(max_time),
+            hard_limit: (planned * HARD_LIMIT_FACTOR).min(max_time),
+            forced_move: position.legal_moves().len() == 1,
+            fixed: false,
+            previous_best_move: None,
+            stability: 0,
+            previous_score: None,
+            score_drop: 0,
+        }
+    }
+
+    // exactly this long, as with movetime
+    pub fn fixed(move_time: Duration) -> Self {
+        TimeManager {
+            start: Instant::now(),
+            soft_limit: move_time,
+            hard_limit: move_time,
+            forced_move: false,
+            fixed: true,
+            previous_best_move: None,
+            stability: 0,
+            previous_score: None,
+            score_drop: 0,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn soft_limit(&self) -> Duration {
+        self.soft_limit
+    }
+
+    pub fn hard_limit(&self) -> Duration {
+        self.hard_limit
+    }
+
+    pub fn hard_limit_reached(&self) -> bool {
+        self.start.elapsed() >= self.hard_limit
+    }
+
+    // called after every finished iteration, whether there is time left for another one
+    pub fn should_continue(&mut self, result: &SearchResult) -> bool {
+        if self.forced_move {
+            return false;
+        }
+
+        if self.fixed {
+            return !self.hard_limit_reached();
+        }
+
+        if result.best_move == self.previous_best_move {
+            self.stability += 1;
+        } else {
+            self.stability = 0;
+        }
+        self.previous_best_move = result.best_move;
+
+        self.score_drop = self
+            .previous_score
+            .map_or(0, |previous| (previous - result.score).clamp(0, 100));
+        self.previous_score = Some(result.score);
+
+        self.start.elapsed() < self.adjusted_limit()
+    }
+
+    // the soft limit stretched or shrunk by how the iterations so far have gone
+    pub fn adjusted_limit(&self) -> Duration {
+        // a best move that keeps changing needs more time to settle, a steady one less
+        let stability_factor = 1.5 - 0.15 * self.stability.min(5) as f64;
+
+        // so does a score that just dropped, a deeper look might still find a way out
+        let score_factor = 1.0 + self.score_drop as f64 / 200.0;
+
+        self.soft_limit
+            .mul_f64(stability_factor * score_factor)
+            .min(self.hard_limit)
+    }
+}
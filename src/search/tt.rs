@@ -0,0 +1,435 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::{
+    player::PieceType,
+    position::{Move, MoveKind},
+    search::MATE_BOUND,
+};
+
+const ENTRIES_PER_BUCKET: usize = 4;
+// 6 bits of age in every entry, so it wraps around after 64 searches
+const AGE_MASK: u8 = 0x3f;
+
+// how the stored score relates to the real one, the search failed high or low if it is not exact
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    // the real score is at most this
+    Upper = 1,
+    // the real score is at least this
+    Lower = 2,
+    Exact = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TtEntry {
+    pub best_move: Option<Move>,
+    // mate scores are already relative to the ply they were probed from
+    pub score: i32,
+    pub depth: u32,
+    pub bound: Bound,
+}
+
+// the key is stored xored with the data so an entry torn by two threads writing at once
+// no longer matches its key and gets ignored, no locks needed
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket {
+    slots: [Slot; ENTRIES_PER_BUCKET],
+}
+
+// data layout: move 16 bits, score 16, depth 8, bound 2, age 6, 16 unused
+// a bound of 0 marks a slot that has never been written
+fn pack(best_move: Option<Move>, score: i32, depth: u32, bound: Bound, age: u8) -> u64 {
+    encode_move(best_move) as u64
+        | (score as i16 as u16 as u64) << 16
+        | (depth.min(u8::MAX as u32) as u64) << 32
+        | (bound as u64) << 40
+        | ((age & AGE_MASK) as u64) << 42
+}
+
+fn unpack_bound(data: u64) -> Option<Bound> {
+    match (data >> 40) & 0b11 {
+        1 => Some(Bound::Upper),
+        2 => Some(Bound::Lower),
+        3 => Some(Bound::Exact),
+        _ => None,
+    }
+}
+
+fn unpack_depth(data: u64) -> u32 {
+    (data >> 32) as u8 as u32
+}
+
+fn unpack_age(data: u64) -> u8 {
+    (data >> 42) as u8 & AGE_MASK
+}
+
+// from, to and a 4 bit flag, 0 meaning no move at all
+fn encode_move(encoded: Option<Move>) -> u16 {
+    let Some(encoded) = encoded else {
+        return 0;
+    };
+
+    let flag = match (encoded.kind, encoded.promotion) {
+        (MoveKind::EnPassant, _) => 2,
+        (MoveKind::Castling, _) => 3,
+        (_, Some(promotion)) => 3 + promotion as u16,
+        (_, None) => 1,
+    };
+
+    encoded.from as u16 | (encoded.to as u16) << 6 | flag << 12
+}
+
+fn decode_move(encoded: u16) -> Option<Move> {
+    let from = (encoded & 0x3f) as usize;
+    let to = (encoded >> 6 & 0x3f) as usize;
+
+    let (kind, promotion) = match encoded >> 12 {
+        0 => return None,
+        1 => (MoveKind::Normal, None),
+        2 => (MoveKind::EnPassant, None),
+        3 => (MoveKind::Castling, None),
+        flag @ 4..=7 => (MoveKind::Normal, Some(PieceType::ALL[flag as usize - 3])),
+        _ => return None,
+    };
+
+    Some(Move {
+        from,
+        to,
+        promotion,
+        kind,
+    })
+}
+
+// mate scores count plies from the root, but in the table they have to count from the node
+// itself so they stay right when the same position is reached at another ply
+pub fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+pub fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+// shared between every search thread, everything goes through atomics so it only ever needs &self
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_MEGABYTES: usize = 16;
+
+    pub fn new(megabytes: usize) -> Self {
+        let bucket_count = (megabytes * 1024 * 1024 / std::mem::size_of::<Bucket>()).max(1);
+
+        TranspositionTable {
+            buckets: (0..bucket_count).map(|_| Bucket::default()).collect(),
+            age: AtomicU8::new(0),
+        }
+    }
+
+    pub fn resize(&mut self, megabytes: usize) {
+        *self = Self::new(megabytes);
+    }
+
+    pub fn clear(&self) {
+        for slot in self.buckets.iter().flat_map(|bucket| &bucket.slots) {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    // called once per search so entries from earlier searches are the first to be replaced
+    pub fn new_search(&self) {
+        let age = self.age.load(Ordering::Relaxed);
+        self.age.store((age + 1) & AGE_MASK, Ordering::Relaxed);
+    }
+
+    pub fn megabytes(&self) -> usize {
+        self.buckets.len() * std::mem::size_of::<Bucket>() / (1024 * 1024)
+    }
+
+    // multiplying instead of masking lets the table be any size, not just powers of two
+    fn bucket(&self, key: u64) -> &Bucket {
+        let index = (key as u128 * self.buckets.len() as u128) >> 64;
+        &self.buckets[index as usize]
+    }
+
+    pub fn probe(&self, key: u64, ply: usize) -> Option<TtEntry> {
+        self.bucket(key).slots.iter().find_map(|slot| {
+            let data = slot.data.load(Ordering::Relaxed);
+            if slot.key.load(Ordering::Relaxed) ^ data != key {
+                return None;
+            }
+
+            Some(TtEntry {
+                best_move: decode_move(data as u16),
+                score: score_from_tt((data >> 16) as u16 as i16 as i32, ply),
+                depth: unpack_depth(data),
+                bound: unpack_bound(data)?,
+            })
+        })
+    }
+
+    pub fn store(
+        &self,
+        key: u64,
+        best_move: Option<Move>,
+        score: i32,
+        depth: u32,
+        bound: Bound,
+        ply: usize,
+    ) {
+        let age = self.age.load(Ordering::Relaxed);
+        let slots = &self.bucket(key).slots;
+
+        let data_of = |slot: &Slot| slot.data.load(Ordering::Relaxed);
+        let matches = |slot: &Slot| slot.key.load(Ordering::Relaxed) ^ data_of(slot) == key;
+
+        // the same position again, else an empty slot, else whatever is least worth keeping,
+        // entries from old searches going first then the shallowest
+        let slot = slots.iter().find(|slot| matches(slot)).unwrap_or_else(|| {
+            slots
+                .iter()
+                .min_by_key(|slot| {
+                    let data = data_of(slot);
+                    if unpack_bound(data).is_none() {
+                        return i32::MIN;
+                    }
+
+                    let relative_age = age.wrapping_sub(unpack_age(data)) & AGE_MASK;
+                    unpack_depth(data) as i32 - 8 * relative_age as i32
+                })
+                .unwrap()
+        });
+
+        let old_data = data_of(slot);
+        let same_position = matches(slot);
+
+        // a shallower result for the same position only replaces an exact one if it is exact itself
+        if same_position
+            && bound != Bound::Exact
+            && unpack_bound(old_data) == Some(Bound::Exact)
+            && unpack_age(old_data) == age
+            && depth + 2 < unpack_depth(old_data)
+        {
+            return;
+        }
+
+        // a fail low has no best move, the one already stored is still the best guess
+        let best_move = match best_move {
+            None if same_position => decode_move(old_data as u16),
+            _ => best_move,
+        };
+
+        let data = pack(best_move, score_to_tt(score, ply), depth, bound, age);
+        slot.key.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    // how full the table is in permille, from a sample of the first thousand slots
+    pub fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+
+        self.buckets
+            .iter()
+            .flat_map(|bucket| &bucket.slots)
+            .take(1000)
+            .filter(|slot| {
+                let data = slot.data.load(Ordering::Relaxed);
+                unpack_bound(data).is_some() && unpack_age(data) == age
+            })
+            .count()
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MEGABYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::MATE_SCORE;
+
+    fn normal_move(from: usize, to: usize) -> Move {
+        Move {
+            from,
+            to,
+            promotion: None,
+            kind: MoveKind::Normal,
+        }
+    }
+
+    #[test]
+    fn pack_and_unpack() {
+        let moves = [
+            None,
+            Some(normal_move(12, 28)),
+            Some(Move {
+                kind: MoveKind::EnPassant,
+                ..normal_move(36, 43)
+            }),
+            Some(Move {
+                kind: MoveKind::Castling,
+                ..normal_move(4, 7)
+            }),
+            Some(Move {
+                promotion: Some(PieceType::Knight),
+                ..normal_move(54, 63)
+            }),
+            Some(Move {
+                promotion: Some(PieceType::Queen),
+                ..normal_move(8, 0)
+            }),
+        ];
+
+        for best_move in moves {
+            for (score, depth, bound, age) in [
+                (0, 0, Bound::Upper, 0),
+                (-1234, 7, Bound::Lower, 63),
+                (MATE_SCORE - 3, 255, Bound::Exact, 17),
+                (-MATE_SCORE + 8, 40, Bound::Exact, 1),
+            ] {
+                let data = pack(best_move, score, depth, bound, age);
+
+                assert_eq!(decode_move(data as u16), best_move);
+                assert_eq!((data >> 16) as u16 as i16 as i32, score);
+                assert_eq!(unpack_depth(data), depth);
+                assert_eq!(unpack_bound(data), Some(bound));
+                assert_eq!(unpack_age(data), age);
+            }
+        }
+
+        // too deep for the byte is kept as deep as it goes, the age wraps around
+        let data = pack(None, 0, 1000, Bound::Exact, 65);
+        assert_eq!(unpack_depth(data), 255);
+        assert_eq!(unpack_age(data), 1);
+    }
+
+    #[test]
+    fn key_is_checked_against_the_data() {
+        let tt = TranspositionTable::new(1);
+        let key = 0x1234_5678_9abc_def0;
+
+        tt.store(key, Some(normal_move(12, 28)), 35, 6, Bound::Exact, 0);
+        assert_eq!(
+            tt.probe(key, 0),
+            Some(TtEntry {
+                best_move: Some(normal_move(12, 28)),
+                score: 35,
+                depth: 6,
+                bound: Bound::Exact,
+            })
+        );
+        assert_eq!(tt.probe(key ^ 1, 0), None);
+
+        // data from another write landing on top of this key, as two threads writing at once could
+        for slot in &tt.bucket(key).slots {
+            if slot.key.load(Ordering::Relaxed) != 0 {
+                slot.data
+                    .store(pack(None, -35, 9, Bound::Lower, 0), Ordering::Relaxed);
+            }
+        }
+        assert_eq!(tt.probe(key, 0), None);
+    }
+
+    #[test]
+    fn mate_scores_are_stored_relative_to_the_node() {
+        let tt = TranspositionTable::new(1);
+
+        // mate 5 plies from the root found at ply 3 is mate 2 plies from the node itself
+        tt.store(1, None, MATE_SCORE - 5, 4, Bound::Exact, 3);
+        tt.store(2, None, -MATE_SCORE + 6, 4, Bound::Exact, 3);
+        tt.store(3, None, 250, 4, Bound::Exact, 3);
+
+        assert_eq!(tt.probe(1, 3).unwrap().score, MATE_SCORE - 5);
+        assert_eq!(tt.probe(1, 1).unwrap().score, MATE_SCORE - 3);
+        assert_eq!(tt.probe(2, 3).unwrap().score, -MATE_SCORE + 6);
+        assert_eq!(tt.probe(2, 7).unwrap().score, -MATE_SCORE + 10);
+        assert_eq!(tt.probe(3, 9).unwrap().score, 250);
+    }
+
+    #[test]
+    fn shallowest_entry_is_replaced() {
+        // less than a bucket, so every key shares the one bucket there is
+        let tt = TranspositionTable::new(0);
+        assert_eq!(tt.buckets.len(), 1);
+
+        for key in 1..=4 {
+            tt.store(key, None, 0, key as u32 + 2, Bound::Exact, 0);
+        }
+        tt.store(5, None, 0, 4, Bound::Exact, 0);
+
+        assert_eq!(tt.probe(1, 0), None);
+        for key in 2..=5 {
+            assert!(tt.probe(key, 0).is_some(), "key {}", key);
+        }
+
+        // the same position again updates its own slot, keeping the move it had
+        tt.store(2, Some(normal_move(1, 18)), 0, 1, Bound::Exact, 0);
+        tt.store(2, None, 0, 2, Bound::Upper, 0);
+        assert_eq!(tt.probe(2, 0).unwrap().best_move, Some(normal_move(1, 18)));
+        for key in 3..=5 {
+            assert!(tt.probe(key, 0).is_some(), "key {}", key);
+        }
+    }
+
+    #[test]
+    fn old_entries_go_first() {
+        let tt = TranspositionTable::new(0);
+
+        for key in 1..=4 {
+            tt.store(key, None, 0, 4 + key as u32, Bound::Exact, 0);
+        }
+
+        // the next search fills the bucket with shallower entries of its own
+        tt.new_search();
+        for key in 5..=8 {
+            tt.store(key, None, 0, 1, Bound::Exact, 0);
+        }
+
+        for key in 1..=4 {
+            assert_eq!(tt.probe(key, 0), None, "key {}", key);
+        }
+        for key in 5..=8 {
+            assert!(tt.probe(key, 0).is_some(), "key {}", key);
+        }
+    }
+
+    #[test]
+    fn deep_exact_entry_survives_a_shallow_bound() {
+        let tt = TranspositionTable::new(1);
+
+        tt.store(7, Some(normal_move(6, 21)), 40, 10, Bound::Exact, 0);
+        tt.store(7, None, -200, 3, Bound::Upper, 0);
+        assert_eq!(tt.probe(7, 0).unwrap().depth, 10);
+
+        // but not one from an earlier search
+        tt.new_search();
+        tt.store(7, None, -200, 3, Bound::Upper, 0);
+        assert_eq!(tt.probe(7, 0).unwrap().bound, Bound::Upper);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    player::{Color, PieceType},
+    position::{Move, MoveKind, Position},
+};
+
+pub const SEE_PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 20000];
+
+pub fn see_value(piece_type: PieceType) -> i32 {
+    SEE_PIECE_VALUES[piece_type as usize]
+}
+
+// the cheapest piece of color in attackers, recaptures always start with the least valuable
+fn least_valuable_attacker(
+    position: &Position,
+    attackers: u64,
+    color: Color,
+) -> Option<(PieceType, u64)> {
+    let player = position.player(color);
+
+    PieceType::ALL.into_iter().find_map(|piece_type| {
+        let piece_attackers = attackers & player.piece_bitboard(piece_type);
+        // isolate the lowest set bit
+        (piece_attackers != 0)
+            .then(|| (piece_type, piece_attackers & piece_attackers.wrapping_neg()))
+    })
+}
+
+// static exchange evaluation, the material outcome of trading off on the destination square
+// with both sides always recapturing with their cheapest piece and able to stop at any point
+pub fn see(position: &Position, exchange_move: Move) -> i32 {
+    if exchange_move.kind == MoveKind::Castling {
+        return 0;
+    }
+
+    let to = exchange_move.to;
+    let Some(moved_piece) = position.piece_at(exchange_move.from) else {
+        return 0;
+    };
+
+    let mut occupied_bitboard = position.occupied_bitboard() & !(1 << exchange_move.from);
+
+    let mut gain = [0; 32];
+    gain[0] = match exchange_move.kind {
+        MoveKind::EnPassant => {
+            let captured_square = match moved_piece.color() {
+                Color::White => to - 8,
+                Color::Black => to + 8,
+            };
+            occupied_bitboard &= !(1 << captured_square);
+            see_value(PieceType::Pawn)
+        }
+        _ => position
+            .piece_at(to)
+            .map_or(0, |captured| see_value(captured.piece_type())),
+    };
+
+    // the piece now standing on the square, and what it is worth to whoever takes it next
+    let mut attacker_value = see_value(moved_piece.piece_type());
+    if let Some(promotion) = exchange_move.promotion {
+        gain[0] += see_value(promotion) - see_value(PieceType::Pawn);
+        attacker_value = see_value(promotion);
+    }
+
+    let mut side = moved_piece.color().opposite_color();
+    let mut depth = 0;
+
+    loop {
+        // recomputed from the shrinking occupancy so sliders behind the last capturer join in
+        let attackers = position.attackers_to(to, occupied_bitboard) & occupied_bitboard;
+
+        let Some((piece_type, attacker_bitboard)) =
+            least_valuable_attacker(position, attackers, side)
+        else {
+            break;
+        };
+
+        // the king can only take last, when nothing defends the square any more
+        if piece_type == PieceType::King
+            && attackers & position.player(side.opposite_color()).all_bitboards() != 0
+        {
+            break;
+        }
+
+        depth += 1;
+        gain[depth] = attacker_value - gain[depth - 1];
+
+        occupied_bitboard &= !attacker_bitboard;
+        attacker_value = see_value(piece_type);
+        side = side.opposite_color();
+
+        if depth == gain.len() - 1 {
+            break;
+        }
+    }
+
+    // walk back up letting each side stand pat if recapturing would lose
+    while depth > 0 {
+        gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        depth -= 1;
+    }
+
+    gain[0]
+}
+
+pub fn see_ge(position: &Position, exchange_move: Move, threshold: i32) -> bool {
+    see(position, exchange_move) >= threshold
+}
+
+// pieces of color that the opponent can win material by capturing
+pub fn hanging_pieces(position: &Position, color: Color) -> u64 {
+    let mut hanging = 0;
+    let mut pieces = position.player(color).all_bitboards() & !position.player(color).king_bitboard;
+    let enemy_bitboard = position.player(color.opposite_color()).all_bitboards();
+    let occupied_bitboard = position.occupied_bitboard();
+
+    while pieces != 0 {
+        let square = pieces.trailing_zeros() as usize;
+        let mut attackers = position.attackers_to(square, occupied_bitboard) & enemy_bitboard;
+
+        while attackers != 0 {
+            let from = attackers.trailing_zeros() as usize;
+            if see(position, Move::new(from, square)) > 0 {
+                hanging |= 1 << square;
+                break;
+            }
+            attackers &= attackers - 1;
+        }
+
+        pieces &= pieces - 1;
+    }
+
+    hanging
+}
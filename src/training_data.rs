@@ -0,0 +1,209 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::{
+    pgn::GameResult,
+    player::{Color, Piece},
+    position::{piece_to_char, Position},
+};
+
+// one position from a self play game, the score from whites point of view
+#[derive(Clone, Debug)]
+pub struct TrainingEntry {
+    pub position: Position,
+    pub score: i16,
+    pub result: GameResult,
+}
+
+// 1 white won, 0.5 drawn, 0 black won
+pub fn result_value(result: GameResult) -> f32 {
+    match result {
+        GameResult::WhiteWin => 1.0,
+        GameResult::BlackWin => 0.0,
+        _ => 0.5,
+    }
+}
+
+fn result_from_value(value: f32) -> GameResult {
+    if value > 0.75 {
+        GameResult::WhiteWin
+    } else if value < 0.25 {
+        GameResult::BlackWin
+    } else {
+        GameResult::Draw
+    }
+}
+
+impl TrainingEntry {
+    // the binary form is fixed size:
+    // occupancy u64, a nibble per occupied square in board order holding the piece index,
+    // side to move, halfmove clock, fullmove number u16, score i16, result (0 black won, 1 draw, 2 white won), padding
+    // castling rights and en passant are not kept, they barely matter to an evaluation
+    pub const SIZE: usize = 32;
+
+    // fen | score | result
+    pub fn to_text_line(&self) -> String {
+        format!(
+            "{} | {} | {:.1}",
+            self.position.to_fen(),
+            self.score,
+            result_value(self.result)
+        )
+    }
+
+    pub fn from_text_line(line: &str) -> Result<Self, String> {
+        let mut fields = line.split('|').map(str::trim);
+
+        let (Some(fen), Some(score), Some(result)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!("expected fen | score | result, got {}", line));
+        };
+
+        Ok(TrainingEntry {
+            position: Position::from_fen(fen)?,
+            score: score
+                .parse()
+                .map_err(|_| format!("invalid score {}", score))?,
+            result: result_from_value(
+                result
+                    .parse()
+                    .map_err(|_| format!("invalid result {}", result))?,
+            ),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        let occupied_bitboard = self.position.occupied_bitboard();
+        bytes[0..8].copy_from_slice(&occupied_bitboard.to_le_bytes());
+
+        let mut squares = occupied_bitboard;
+        let mut nibble = 0;
+        while squares != 0 {
+            let board_index = squares.trailing_zeros() as usize;
+            let piece = self.position.piece_at(board_index).unwrap() as u8;
+            bytes[8 + nibble / 2] |= piece << (4 * (nibble % 2));
+
+            nibble += 1;
+            squares &= squares - 1;
+        }
+
+        bytes[24] = self.position.side_to_move as u8;
+        bytes[25] = self.position.halfmove_clock.min(255) as u8;
+        bytes[26..28].copy_from_slice(&(self.position.fullmove_number as u16).to_le_bytes());
+        bytes[28..30].copy_from_slice(&self.score.to_le_bytes());
+        bytes[30] = match self.result {
+            GameResult::BlackWin => 0,
+            GameResult::WhiteWin => 2,
+            _ => 1,
+        };
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Result<Self, String> {
+        let occupied_bitboard = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        if occupied_bitboard.count_ones() > 32 {
+            return Err("more than 32 pieces".to_owned());
+        }
+
+        let mut board = [None; 64];
+        let mut squares = occupied_bitboard;
+        let mut nibble = 0;
+        while squares != 0 {
+            let piece_index = (bytes[8 + nibble / 2] >> (4 * (nibble % 2))) & 0xf;
+            let piece = *Piece::ALL
+                .get(piece_index as usize)
+                .ok_or_else(|| format!("invalid piece {}", piece_index))?;
+            board[squares.trailing_zeros() as usize] = Some(piece);
+
+            nibble += 1;
+            squares &= squares - 1;
+        }
+
+        // rebuilt through a fen so every key and table is set up the usual way
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match board[rank * 8 + file] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece_to_char(piece));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        let side_to_move = if bytes[24] == Color::White as u8 {
+            'w'
+        } else {
+            'b'
+        };
+        let fullmove_number = u16::from_le_bytes([bytes[26], bytes[27]]);
+        fen.push_str(&format!(
+            " {} - - {} {}",
+            side_to_move,
+            bytes[25],
+            fullmove_number.max(1)
+        ));
+
+        Ok(TrainingEntry {
+            position: Position::from_fen(&fen)?,
+            score: i16::from_le_bytes([bytes[28], bytes[29]]),
+            result: match bytes[30] {
+                0 => GameResult::BlackWin,
+                2 => GameResult::WhiteWin,
+                _ => GameResult::Draw,
+            },
+        })
+    }
+}
+
+pub fn write_text<W: Write>(writer: &mut W, entries: &[TrainingEntry]) -> io::Result<()> {
+    for entry in entries {
+        writeln!(writer, "{}", entry.to_text_line())?;
+    }
+
+    Ok(())
+}
+
+pub fn write_binary<W: Write>(writer: &mut W, entries: &[TrainingEntry]) -> io::Result<()> {
+    for entry in entries {
+        writer.write_all(&entry.to_bytes())?;
+    }
+
+    Ok(())
+}
+
+// lines that do not parse are skipped
+pub fn read_text<R: BufRead>(reader: R) -> io::Result<Vec<TrainingEntry>> {
+    let mut entries = Vec::new();
+
+    for line in reader.lines() {
+        if let Ok(entry) = TrainingEntry::from_text_line(&line?) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+pub fn read_binary<R: Read>(mut reader: R) -> io::Result<Vec<TrainingEntry>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    Ok(bytes
+        .chunks_exact(TrainingEntry::SIZE)
+        .filter_map(|chunk| TrainingEntry::from_bytes(chunk.try_into().unwrap()).ok())
+        .collect())
+}
@@ -0,0 +1,92 @@
+use crate::player::{Color, Piece};
+
+pub struct ZobristKeys {
+    // indexed by Piece as usize then board index
+    pub pieces: [[u64; 64]; 12],
+    pub black_to_move: u64,
+    // one key per combination of the 4 castling right bits
+    pub castling: [u64; 16],
+    pub en_passant_file: [u64; 8],
+}
+
+// splitmix64, good enough spread for hashing and usable in a const fn
+const fn next_random(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+    (state, z ^ (z >> 31))
+}
+
+impl ZobristKeys {
+    pub const fn new(seed: u64) -> Self {
+        let mut state = seed;
+        let mut random;
+
+        let mut pieces = [[0; 64]; 12];
+        let mut piece = 0;
+        while piece < 12 {
+            let mut square = 0;
+            while square < 64 {
+                (state, random) = next_random(state);
+                pieces[piece][square] = random;
+                square += 1;
+            }
+            piece += 1;
+        }
+
+        (state, random) = next_random(state);
+        let black_to_move = random;
+
+        // castling keys are built from 4 base keys so toggling one right is a single xor
+        let mut castling_base = [0; 4];
+        let mut i = 0;
+        while i < 4 {
+            (state, random) = next_random(state);
+            castling_base[i] = random;
+            i += 1;
+        }
+
+        let mut castling = [0; 16];
+        let mut rights = 0;
+        while rights < 16 {
+            let mut bit = 0;
+            while bit < 4 {
+                if rights & (1 << bit) != 0 {
+                    castling[rights] ^= castling_base[bit];
+                }
+                bit += 1;
+            }
+            rights += 1;
+        }
+
+        let mut en_passant_file = [0; 8];
+        let mut file = 0;
+        while file < 8 {
+            (state, random) = next_random(state);
+            en_passant_file[file] = random;
+            file += 1;
+        }
+
+        ZobristKeys {
+            pieces,
+            black_to_move,
+            castling,
+            en_passant_file,
+        }
+    }
+
+    pub fn piece(&self, piece: Piece, board_index: usize) -> u64 {
+        self.pieces[piece as usize][board_index]
+    }
+
+    pub fn side(&self, color: Color) -> u64 {
+        match color {
+            Color::White => 0,
+            Color::Black => self.black_to_move,
+        }
+    }
+}
+
+pub static ZOBRIST_KEYS: ZobristKeys = ZobristKeys::new(0x3243f6a8885a308d);
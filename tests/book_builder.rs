@@ -0,0 +1,24 @@
+use chess_engine::{
+    book_builder::BookBuilder,
+    pgn::{GameResult, PgnGame},
+};
+
+fn game(moves: &[&str], result: GameResult) -> PgnGame {
+    PgnGame {
+        tags: Vec::new(),
+        moves: moves.iter().map(|san| san.to_string()).collect(),
+        result,
+    }
+}
+
+#[test]
+fn broken_game_adds_nothing() {
+    let mut builder = BookBuilder::new(20, 1, 0.0);
+
+    assert!(!builder.add_game(&game(&["e4", "e5", "Nf3", "Qxz9"], GameResult::WhiteWin)));
+    assert!(builder.stats.is_empty());
+
+    assert!(builder.add_game(&game(&["e4", "e5", "Nf3"], GameResult::WhiteWin)));
+    assert_eq!(builder.stats.len(), 3);
+    assert!(builder.stats.values().all(|stats| stats.games() == 1));
+}
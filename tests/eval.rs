@@ -0,0 +1,54 @@
+use chess_engine::{
+    eval::{eval, params::EvalParams, trace::eval_trace},
+    position::Position,
+};
+
+const FENS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "6k1/5ppp/8/8/3Q4/8/5PPP/6K1 b - - 0 1",
+];
+
+#[test]
+fn eval_is_color_symmetric() {
+    for fen in FENS {
+        let position = Position::from_fen(fen).unwrap();
+
+        assert_eq!(eval(&position), eval(&position.mirrored()), "{}", fen);
+    }
+}
+
+#[test]
+fn starting_position_is_balanced() {
+    assert_eq!(eval(&Position::new()), 0);
+}
+
+#[test]
+fn trace_adds_up_to_eval() {
+    for fen in FENS {
+        let position = Position::from_fen(fen).unwrap();
+
+        assert_eq!(eval_trace(&position).score(), eval(&position), "{}", fen);
+    }
+}
+
+#[test]
+fn weight_index_matches_weight_list() {
+    let mut params = EvalParams::default();
+    let count = params.weight_count();
+    assert_eq!(count, params.weights_mut().len());
+
+    for index in 0..count {
+        *params.weight_mut(index) = index as i32;
+    }
+
+    let weights: Vec<i32> = params
+        .weights_mut()
+        .into_iter()
+        .map(|weight| *weight)
+        .collect();
+    assert_eq!(weights, (0..count as i32).collect::<Vec<i32>>());
+}
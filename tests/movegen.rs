@@ -0,0 +1,105 @@
+use chess_engine::position::Position;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+// a fixed xorshift so the random games are the same on every run
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[test]
+fn perft_starting_position() {
+    let mut position = Position::new();
+
+    for (depth, nodes) in [(1, 20), (2, 400), (3, 8902), (4, 197281)] {
+        assert_eq!(position.perft(depth), nodes, "depth {}", depth);
+    }
+}
+
+#[test]
+fn perft_kiwipete() {
+    let mut position = Position::from_fen(KIWIPETE).unwrap();
+
+    for (depth, nodes) in [(1, 48), (2, 2039), (3, 97862)] {
+        assert_eq!(position.perft(depth), nodes, "depth {}", depth);
+    }
+}
+
+#[test]
+fn perft_en_passant_and_promotions() {
+    let cases = [
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, 43238),
+        (
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            3,
+            9467,
+        ),
+        (
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            3,
+            62379,
+        ),
+    ];
+
+    for (fen, depth, nodes) in cases {
+        let mut position = Position::from_fen(fen).unwrap();
+        assert_eq!(position.perft(depth), nodes, "{}", fen);
+    }
+}
+
+#[test]
+fn incremental_keys_match_recompute() {
+    let mut state = 0x9e37_79b9_7f4a_7c15;
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        KIWIPETE,
+    ] {
+        for _ in 0..20 {
+            let mut position = Position::from_fen(fen).unwrap();
+            let mut fens = vec![position.to_fen()];
+
+            for _ in 0..80 {
+                let moves = position.legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+
+                let played_move = moves[next_random(&mut state) as usize % moves.len()];
+                position.make_move(played_move);
+                assert_eq!(position.zobrist_key, position.compute_zobrist_key());
+                assert_eq!(position.pawn_key, position.compute_pawn_key());
+                fens.push(position.to_fen());
+            }
+
+            fens.pop();
+            while let Some(fen) = fens.pop() {
+                position.unmake_move();
+                assert_eq!(position.to_fen(), fen);
+                assert_eq!(position.zobrist_key, position.compute_zobrist_key());
+                assert_eq!(position.pawn_key, position.compute_pawn_key());
+            }
+        }
+    }
+}
+
+#[test]
+fn chess960_castling_with_the_king_already_in_place() {
+    // king on g1 and rook on h1, kingside castling only moves the rook
+    let position = Position::from_fen("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").unwrap();
+    let castling = position.move_from_uci("g1h1").unwrap();
+
+    assert_eq!(castling.to_uci(true), "g1h1");
+    assert_eq!(castling.to_string(), "g1h1");
+    assert_eq!(position.move_from_uci("g1g1"), None);
+}
+
+#[test]
+fn castling_rights_need_the_king_on_its_back_rank() {
+    assert!(Position::from_fen("r6r/8/8/8/8/8/4K3/R6R w HAha - 0 1").is_err());
+    assert!(Position::from_fen("r6r/4k3/8/8/8/8/8/R3K2R w KQkq - 0 1").is_err());
+    assert!(Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1").is_ok());
+}
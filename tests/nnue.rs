@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use chess_engine::{
+    nnue::{accumulator::Accumulator, simd, Network, HIDDEN_SIZE, MAX_OUTPUT_BIAS, QA},
+    position::Position,
+    search::MATE_BOUND,
+};
+
+const FENS: [&str; 4] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9",
+];
+
+// a fixed xorshift so the weights are the same on every run
+fn next_random(state: &mut u64) -> i16 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state >> 48) as i16
+}
+
+// small random weights, enough to tell every feature apart
+fn random_network() -> Network {
+    let mut state = 0x2545f4914f6cdd1d;
+    let mut network = Network::zeroed();
+
+    for weight in network.feature_weights.iter_mut() {
+        *weight = next_random(&mut state) % 64;
+    }
+    for bias in network.feature_biases.iter_mut() {
+        *bias = next_random(&mut state) % 64 + 64;
+    }
+    for weight in network.output_weights.iter_mut() {
+        *weight = next_random(&mut state) % 64;
+    }
+    network.output_bias = 100;
+
+    network
+}
+
+// every move made and unmade to depth 2 has to leave the same accumulator as a full refresh
+fn check_accumulators(position: &mut Position, network: &Network, depth: u32) {
+    if depth == 0 {
+        return;
+    }
+
+    for legal_move in position.legal_moves() {
+        position.make_move(legal_move);
+        assert_eq!(
+            position.nnue.as_ref().unwrap().current(),
+            &Accumulator::refreshed(network, position),
+            "after {} in {}",
+            legal_move,
+            position.to_fen()
+        );
+
+        check_accumulators(position, network, depth - 1);
+
+        position.unmake_move();
+        assert_eq!(
+            position.nnue.as_ref().unwrap().current(),
+            &Accumulator::refreshed(network, position),
+            "after taking back {} in {}",
+            legal_move,
+            position.to_fen()
+        );
+    }
+}
+
+#[test]
+fn incremental_accumulator_matches_refresh() {
+    let network = Arc::new(random_network());
+
+    for fen in FENS {
+        let mut position = Position::from_fen(fen).unwrap();
+        position.enable_nnue(network.clone());
+
+        check_accumulators(&mut position, &network, 2);
+    }
+}
+
+#[test]
+fn network_round_trips_through_bytes() {
+    let network = random_network();
+    let read_back = Network::from_bytes(&network.to_bytes()).unwrap();
+
+    assert_eq!(read_back.feature_weights, network.feature_weights);
+    assert_eq!(read_back.feature_biases, network.feature_biases);
+    assert_eq!(read_back.output_weights, network.output_weights);
+    assert_eq!(read_back.output_bias, network.output_bias);
+}
+
+// whichever routines this cpu picks have to agree with the scalar ones
+#[test]
+fn vector_routines_match_scalar() {
+    let mut state = 0x9e3779b97f4a7c15;
+
+    for _ in 0..100 {
+        // full range values, so wrapping and clamping on both ends get exercised
+        let values: Vec<i16> = (0..HIDDEN_SIZE).map(|_| next_random(&mut state)).collect();
+        let weights: Vec<i16> = (0..HIDDEN_SIZE).map(|_| next_random(&mut state)).collect();
+
+        let mut added = values.clone();
+        let mut scalar_added = values.clone();
+        simd::add_assign(&mut added, &weights);
+        simd::scalar::add_assign(&mut scalar_added, &weights);
+        assert_eq!(added, scalar_added);
+
+        let mut subtracted = values.clone();
+        let mut scalar_subtracted = values.clone();
+        simd::sub_assign(&mut subtracted, &weights);
+        simd::scalar::sub_assign(&mut scalar_subtracted, &weights);
+        assert_eq!(subtracted, scalar_subtracted);
+
+        // the accumulator never gets far outside the clamp, and the output weights stay small
+        let values: Vec<i16> = values.iter().map(|value| value % (2 * QA as i16)).collect();
+        let weights: Vec<i16> = weights.iter().map(|weight| weight % 128).collect();
+        assert_eq!(
+            simd::clipped_relu_dot(&values, &weights),
+            simd::scalar::clipped_relu_dot(&values, &weights)
+        );
+    }
+}
+
+#[test]
+fn evaluation_is_the_same_with_and_without_simd() {
+    let network = Arc::new(random_network());
+
+    for fen in FENS {
+        let mut position = Position::from_fen(fen).unwrap();
+        position.enable_nnue(network.clone());
+
+        let with_simd = position.nnue_eval().unwrap();
+        simd::set_simd_enabled(false);
+        let without_simd = position.nnue_eval().unwrap();
+        simd::set_simd_enabled(true);
+
+        assert_eq!(with_simd, without_simd, "{}", fen);
+    }
+}
+
+// every output weight and the bias as big as they go, the sum no longer fits an i32 once scaled
+fn saturated_network(sign: i16) -> Network {
+    let mut network = Network::zeroed();
+    network.feature_biases.fill(QA as i16);
+    network.output_weights.fill(sign * i16::MAX);
+    network.output_bias = sign as i32 * MAX_OUTPUT_BIAS;
+
+    network
+}
+
+#[test]
+fn saturated_network_does_not_overflow() {
+    for sign in [1, -1] {
+        let network = Network::from_bytes(&saturated_network(sign).to_bytes()).unwrap();
+        let mut position = Position::new();
+        position.enable_nnue(Arc::new(network));
+
+        let score = position.nnue_eval().unwrap();
+        assert_eq!(score.signum(), sign as i32);
+        assert!(score.abs() < MATE_BOUND);
+    }
+}
+
+#[test]
+fn output_bias_out_of_range_is_rejected() {
+    let mut network = saturated_network(1);
+    network.output_bias = MAX_OUTPUT_BIAS + 1;
+
+    assert!(Network::from_bytes(&network.to_bytes()).is_err());
+}
@@ -0,0 +1,89 @@
+use chess_engine::{
+    eval::{
+        params::EvalParams,
+        pawns::{evaluate_pawns, PawnHashTable, PawnTerms},
+        Score,
+    },
+    position::Position,
+};
+
+fn white_terms(fen: &str) -> PawnTerms {
+    evaluate_pawns(&Position::from_fen(fen).unwrap(), &EvalParams::default()).terms[0]
+}
+
+#[test]
+fn doubled_pawns() {
+    let params = EvalParams::default();
+    let terms = white_terms("4k3/8/8/8/8/4P3/4P3/4K3 w - - 0 1");
+
+    // only the front pawn counts, and both are isolated too
+    assert_eq!(terms.doubled, params.doubled_pawn);
+    assert_eq!(terms.isolated, params.isolated_pawn * 2);
+}
+
+#[test]
+fn isolated_pawns() {
+    let params = EvalParams::default();
+
+    assert_eq!(
+        white_terms("4k3/8/8/8/8/8/PP5P/4K3 w - - 0 1").isolated,
+        params.isolated_pawn
+    );
+    assert_eq!(
+        white_terms("4k3/8/8/8/8/8/PPP5/4K3 w - - 0 1").isolated,
+        Score::ZERO
+    );
+}
+
+#[test]
+fn passed_pawns() {
+    let params = EvalParams::default();
+    let position = Position::from_fen("4k3/8/8/3P4/p7/8/8/4K3 w - - 0 1").unwrap();
+    let entry = evaluate_pawns(&position, &params);
+
+    // d5 and a4, black gets its pawns back the right way up
+    assert_eq!(entry.passed_pawns, [1 << 35, 1 << 24]);
+    assert_eq!(entry.terms[0].passed, params.passed_pawn[4]);
+    assert_eq!(entry.terms[1].passed, params.passed_pawn[4]);
+
+    // a pawn on a neighbouring file in front stops it being passed
+    let position = Position::from_fen("4k3/2p5/8/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(evaluate_pawns(&position, &params).passed_pawns[0], 0);
+}
+
+#[test]
+fn candidate_passed_pawns() {
+    let params = EvalParams::default();
+
+    // d4 has c3 to help it past e6, c3 itself is already passed
+    let terms = white_terms("4k3/8/4p3/8/3P4/2P5/8/4K3 w - - 0 1");
+    assert_eq!(terms.candidate, params.candidate_passed_pawn[3]);
+
+    // with c6 as well there are two sentries and one helper
+    let terms = white_terms("4k3/8/2p1p3/8/3P4/2P5/8/4K3 w - - 0 1");
+    assert_eq!(terms.candidate, Score::ZERO);
+}
+
+#[test]
+fn pawn_hash_hit_matches_recompute() {
+    let params = EvalParams::default();
+    let mut table = PawnHashTable::new(1 << 10);
+    let mut position =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+
+    // every structure gets probed twice, the second time comes out of the table
+    for _ in 0..2 {
+        for legal_move in position.legal_moves() {
+            position.make_move(legal_move);
+
+            let probed = table.probe(&position, &params);
+            let computed = evaluate_pawns(&position, &params);
+            assert_eq!(probed.key, computed.key);
+            assert_eq!(probed.terms, computed.terms);
+            assert_eq!(probed.passed_pawns, computed.passed_pawns);
+
+            position.unmake_move();
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use chess_engine::pgn::{GameResult, PgnReader};
+
+#[test]
+fn bad_utf8_line_does_not_end_the_file() {
+    let mut pgn = b"[Event \"one\"]\n[White \"M\xfcller\"]\n\n1. e4 e5 2. Nf3 1-0\n\n".to_vec();
+    pgn.extend_from_slice(b"[Event \"two\"]\r\n\r\n1. d4 d5 0-1\r\n");
+
+    let games: Vec<_> = PgnReader::new(&pgn[..]).collect();
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].moves, ["e4", "e5", "Nf3"]);
+    assert_eq!(games[0].result, GameResult::WhiteWin);
+    assert_eq!(games[1].tag("Event"), Some("two"));
+    assert_eq!(games[1].moves, ["d4", "d5"]);
+    assert_eq!(games[1].result, GameResult::BlackWin);
+}
+
+#[test]
+fn stray_closing_bracket_keeps_variations_out() {
+    let pgn = "[Event \"one\"]\n\n1. e4 ) e5 (1... c5 2. Nf3) 2. Nf3 (2. d4) Nc6 1-0\n";
+    let games: Vec<_> = PgnReader::new(pgn.as_bytes()).collect();
+
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].moves, ["e4", "e5", "Nf3", "Nc6"]);
+}
+
+#[test]
+fn game_without_movetext_ends_at_the_next_games_tags() {
+    let pgn = "[Event \"one\"]\n[Result \"*\"]\n\n[Event \"two\"]\n[Result \"1-0\"]\n\n1. e4 1-0\n";
+    let games: Vec<_> = PgnReader::new(pgn.as_bytes()).collect();
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].tag("Event"), Some("one"));
+    assert!(games[0].moves.is_empty());
+    assert_eq!(games[1].tag("Event"), Some("two"));
+    assert_eq!(games[1].tags.len(), 2);
+    assert_eq!(games[1].moves, ["e4"]);
+}
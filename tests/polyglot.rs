@@ -0,0 +1,114 @@
+use chess_engine::{
+    polyglot::{encode_polyglot_move, polyglot_key, PolyglotBook, PolyglotEntry},
+    position::Position,
+};
+
+// the reference keys from the polyglot book format description
+const REFERENCE_KEYS: [(&str, u64); 9] = [
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        0x463b96181691fc9c,
+    ),
+    (
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        0x823c9b50fd114196,
+    ),
+    (
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+        0x0756b94461c50fb0,
+    ),
+    (
+        "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+        0x662fafb965db29d4,
+    ),
+    (
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        0x22a48b5a8e47ff78,
+    ),
+    (
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3",
+        0x652a607ca3f242c1,
+    ),
+    (
+        "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+        0x00fdd303c946bdd9,
+    ),
+    (
+        "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+        0x3c8123ea7b067637,
+    ),
+    (
+        "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+        0x5c3f9b829b279560,
+    ),
+];
+
+#[test]
+fn starting_position_key() {
+    assert_eq!(polyglot_key(&Position::new()), 0x463b96181691fc9c);
+}
+
+#[test]
+fn reference_keys() {
+    for (fen, key) in REFERENCE_KEYS {
+        let position = Position::from_fen(fen).unwrap();
+
+        assert_eq!(polyglot_key(&position), key, "{}", fen);
+    }
+}
+
+#[test]
+fn reference_keys_after_moves() {
+    // the same positions reached by playing the moves, so the en passant square and castling
+    // rights come from make_move rather than the fen
+    let mut position = Position::new();
+
+    for (uci, (_, key)) in ["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"]
+        .iter()
+        .zip(&REFERENCE_KEYS[1..7])
+    {
+        position.make_move(position.move_from_uci(uci).unwrap());
+        assert_eq!(polyglot_key(&position), *key, "{}", uci);
+    }
+}
+
+#[test]
+fn weighted_random_move_follows_the_weights() {
+    let position = Position::new();
+    let key = polyglot_key(&position);
+    let e4 = position.move_from_uci("e2e4").unwrap();
+    let d4 = position.move_from_uci("d2d4").unwrap();
+
+    let book = PolyglotBook {
+        entries: [(e4, 3), (d4, 1)]
+            .map(|(book_move, weight)| PolyglotEntry {
+                key,
+                raw_move: encode_polyglot_move(book_move),
+                weight,
+                learn: 0,
+            })
+            .to_vec(),
+    };
+
+    let picks: Vec<_> = (0..8)
+        .map(|random| book.weighted_random_move(&position, random).unwrap())
+        .collect();
+    assert_eq!(picks, [e4, e4, e4, d4, e4, e4, e4, d4]);
+}
+
+#[test]
+fn truncated_book_is_rejected() {
+    let position = Position::new();
+    let entry = PolyglotEntry {
+        key: polyglot_key(&position),
+        raw_move: encode_polyglot_move(position.move_from_uci("e2e4").unwrap()),
+        weight: 1,
+        learn: 0,
+    };
+    let mut bytes = [entry.to_bytes(), entry.to_bytes()].concat();
+
+    assert_eq!(PolyglotBook::from_bytes(&bytes).unwrap().entries.len(), 2);
+
+    bytes.pop();
+    assert!(PolyglotBook::from_bytes(&bytes).is_err());
+}
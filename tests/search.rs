@@ -0,0 +1,266 @@
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
+
+use chess_engine::{
+    position::Position,
+    search::{
+        format_score, search,
+        smp::ThreadPool,
+        time::{TimeControl, TimeManager, MAX_TIME_USAGE, MOVE_OVERHEAD},
+        tt::TranspositionTable,
+        SearchLimits, SearchResult, Searcher,
+    },
+};
+
+// 1. Nf6+ gxf6 2. Bxf7 mates
+const MATE_IN_TWO: &str = "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1";
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn new_searcher() -> Searcher {
+    Searcher::new(
+        Arc::new(TranspositionTable::default()),
+        Arc::new(AtomicBool::new(false)),
+    )
+}
+
+fn depth_limit(depth: u32) -> SearchLimits {
+    SearchLimits {
+        depth: Some(depth),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn finds_mate_in_two() {
+    let mut position = Position::from_fen(MATE_IN_TWO).unwrap();
+    let result = search(&mut position, &depth_limit(6), |_| {});
+
+    assert_eq!(format_score(result.score), "mate 2");
+    assert_eq!(result.best_move, position.move_from_uci("d5f6"));
+
+    // the pv has to actually end in mate
+    assert_eq!(result.pv.len(), 3);
+    for pv_move in &result.pv {
+        position.make_move(*pv_move);
+    }
+    assert!(position.is_checkmate());
+}
+
+#[test]
+fn stops_at_the_depth_limit() {
+    let mut position = Position::from_fen(KIWIPETE).unwrap();
+    let mut depths = Vec::new();
+    let result = search(&mut position, &depth_limit(4), |result| {
+        depths.push(result.depth)
+    });
+
+    assert_eq!(depths, [1, 2, 3, 4]);
+    assert_eq!(result.depth, 4);
+}
+
+#[test]
+fn stops_at_the_node_limit() {
+    let mut position = Position::from_fen(KIWIPETE).unwrap();
+    let mut searcher = new_searcher();
+    let limits = SearchLimits {
+        nodes: Some(5000),
+        ..Default::default()
+    };
+    let result = searcher.search(&mut position, &limits, |_| {});
+
+    assert!(searcher.nodes <= 5000, "{} nodes searched", searcher.nodes);
+    assert!(result.nodes <= 5000);
+    // the last finished iteration is still there to play from
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn only_searches_the_given_root_moves() {
+    let mut position = Position::new();
+    let search_moves = vec![
+        position.move_from_uci("a2a3").unwrap(),
+        position.move_from_uci("h2h4").unwrap(),
+    ];
+
+    let mut searcher = new_searcher();
+    searcher.multi_pv = 4;
+    let limits = SearchLimits {
+        depth: Some(4),
+        search_moves: search_moves.clone(),
+        ..Default::default()
+    };
+    let result = searcher.search(&mut position, &limits, |_| {});
+
+    assert!(search_moves.contains(&result.best_move.unwrap()));
+    assert_eq!(result.lines.len(), 2);
+    for line in &result.lines {
+        assert!(search_moves.contains(&line.pv[0]));
+    }
+}
+
+#[test]
+fn multi_pv_gives_distinct_sorted_lines() {
+    let mut position = Position::from_fen(KIWIPETE).unwrap();
+    let mut searcher = new_searcher();
+    searcher.multi_pv = 4;
+    let result = searcher.search(&mut position, &depth_limit(5), |_| {});
+
+    assert_eq!(result.lines.len(), 4);
+    assert_eq!(result.lines[0].pv, result.pv);
+    assert_eq!(result.lines[0].score, result.score);
+
+    for (index, line) in result.lines.iter().enumerate() {
+        assert!(position.legal_moves().contains(&line.pv[0]));
+        assert!(result.lines[..index]
+            .iter()
+            .all(|earlier| earlier.pv[0] != line.pv[0]));
+    }
+    assert!(result
+        .lines
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+fn clock(time: u64, increment: u64, moves_to_go: Option<u32>) -> TimeControl {
+    TimeControl {
+        white_time: Duration::from_millis(time),
+        black_time: Duration::from_millis(time),
+        white_increment: Duration::from_millis(increment),
+        black_increment: Duration::from_millis(increment),
+        moves_to_go,
+    }
+}
+
+#[test]
+fn time_limits_stay_within_the_clock() {
+    let position = Position::new();
+
+    for (time, increment, moves_to_go) in [
+        (60_000, 0, None),
+        (60_000, 2_000, None),
+        (1_000, 5_000, None),
+        (60_000, 0, Some(1)),
+        (60_000, 1_000, Some(1)),
+        (40, 0, Some(1)),
+    ] {
+        let time_manager = TimeManager::new(&clock(time, increment, moves_to_go), &position);
+        let available = Duration::from_millis(time).saturating_sub(MOVE_OVERHEAD);
+        let clock = format!("{}+{} {:?}", time, increment, moves_to_go);
+
+        assert!(
+            time_manager.soft_limit() <= time_manager.hard_limit(),
+            "{}",
+            clock
+        );
+        assert!(
+            time_manager.hard_limit() <= available.mul_f64(MAX_TIME_USAGE),
+            "{}",
+            clock
+        );
+        assert!(
+            time_manager.adjusted_limit() <= time_manager.hard_limit(),
+            "{}",
+            clock
+        );
+    }
+}
+
+#[test]
+fn clock_below_the_overhead_still_gives_a_move() {
+    let mut position = Position::from_fen(KIWIPETE).unwrap();
+    let limits = SearchLimits {
+        time_control: Some(clock(MOVE_OVERHEAD.as_millis() as u64 / 2, 0, None)),
+        ..Default::default()
+    };
+    let result = search(&mut position, &limits, |_| {});
+
+    assert!(position.legal_moves().contains(&result.best_move.unwrap()));
+}
+
+#[test]
+fn forced_move_is_played_at_once() {
+    // the rook leaves the king a single square
+    let mut position = Position::from_fen("k7/8/8/8/8/8/8/1R5K b - - 0 1").unwrap();
+    assert_eq!(position.legal_moves().len(), 1);
+
+    let mut time_manager = TimeManager::new(&clock(60_000, 0, None), &position);
+    let result = search(&mut position, &depth_limit(1), |_| {});
+    assert!(!time_manager.should_continue(&result));
+}
+
+#[test]
+fn unsettled_search_gets_more_time() {
+    let position = Position::new();
+    let mut time_manager = TimeManager::new(&clock(60_000, 0, None), &position);
+    let e2e4 = position.move_from_uci("e2e4");
+    let d2d4 = position.move_from_uci("d2d4");
+
+    let result = |best_move, score| SearchResult {
+        best_move,
+        score,
+        ..Default::default()
+    };
+
+    // a steady best move and score use less than the planned time
+    for _ in 0..6 {
+        time_manager.should_continue(&result(e2e4, 30));
+    }
+    let steady = time_manager.adjusted_limit();
+    assert!(steady < time_manager.soft_limit());
+
+    // a score that drops buys more
+    time_manager.should_continue(&result(e2e4, -70));
+    let dropped = time_manager.adjusted_limit();
+    assert!(dropped > steady);
+
+    // and a best move that changes more again
+    time_manager.should_continue(&result(d2d4, -70));
+    assert!(time_manager.adjusted_limit() > dropped);
+    assert!(time_manager.adjusted_limit() <= time_manager.hard_limit());
+}
+
+#[test]
+fn thread_pool_searches_to_the_depth_limit() {
+    let position = Position::from_fen(KIWIPETE).unwrap();
+    let mut pool = ThreadPool::new(
+        4,
+        Arc::new(TranspositionTable::default()),
+        Arc::new(AtomicBool::new(false)),
+    );
+    let result = pool.search(&position, &depth_limit(6), |_| {});
+
+    assert!(position.legal_moves().contains(&result.best_move.unwrap()));
+    assert_eq!(result.depth, 6);
+
+    // every thread took part, and the count covers all of them
+    let thread_nodes = pool.thread_nodes();
+    assert_eq!(thread_nodes.len(), 4);
+    assert!(thread_nodes.iter().all(|&nodes| nodes > 0));
+    assert_eq!(result.nodes, thread_nodes.iter().sum::<u64>());
+}
+
+#[test]
+fn thread_count_follows_set_threads() {
+    let mut pool = ThreadPool::new(
+        4,
+        Arc::new(TranspositionTable::default()),
+        Arc::new(AtomicBool::new(false)),
+    );
+    assert_eq!(pool.threads(), 4);
+
+    pool.set_threads(2);
+    assert_eq!(pool.threads(), 2);
+    pool.set_threads(6);
+    assert_eq!(pool.threads(), 6);
+    // there is always the main thread
+    pool.set_threads(0);
+    assert_eq!(pool.threads(), 1);
+
+    // a pool that shrank and grew again still searches with all of them
+    pool.set_threads(3);
+    let result = pool.search(&Position::new(), &depth_limit(4), |_| {});
+    assert!(result.best_move.is_some());
+    assert_eq!(pool.thread_nodes().len(), 3);
+}
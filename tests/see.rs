@@ -0,0 +1,79 @@
+use chess_engine::{
+    player::Color,
+    position::{parse_square, Position},
+    see::{hanging_pieces, see, see_ge},
+};
+
+fn see_of(fen: &str, uci: &str) -> i32 {
+    let position = Position::from_fen(fen).unwrap();
+    let exchange_move = position.move_from_uci(uci).unwrap();
+
+    see(&position, exchange_move)
+}
+
+#[test]
+fn undefended_capture() {
+    assert_eq!(
+        see_of("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+        100
+    );
+}
+
+#[test]
+fn losing_capture_with_batteries_behind() {
+    // the knight only wins a pawn, the rook, queen and bishop lined up behind change nothing
+    assert_eq!(
+        see_of(
+            "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+            "d3e5"
+        ),
+        -220
+    );
+}
+
+#[test]
+fn x_ray_recapture() {
+    // the second rook only sees d5 once the first one has gone
+    assert_eq!(see_of("6k1/3r4/8/3p4/8/8/3R4/3R2K1 w - - 0 1", "d2d5"), 100);
+    assert_eq!(
+        see_of("3r2k1/3r4/8/3p4/8/8/3R4/6K1 w - - 0 1", "d2d5"),
+        -400
+    );
+}
+
+#[test]
+fn en_passant() {
+    assert_eq!(see_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    assert_eq!(see_of("4k3/2p5/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 0);
+}
+
+#[test]
+fn promotions() {
+    assert_eq!(see_of("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), 800);
+    assert_eq!(see_of("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), -100);
+    assert_eq!(see_of("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"), 1300);
+}
+
+#[test]
+fn king_recaptures_when_nothing_defends() {
+    // the queen taking back on d2 would lose itself to the king
+    let fen = "3qk3/8/8/8/8/8/3p4/3RK3 w - - 0 1";
+    assert_eq!(see_of(fen, "d1d2"), 100);
+    let position = Position::from_fen(fen).unwrap();
+    assert!(see_ge(
+        &position,
+        position.move_from_uci("d1d2").unwrap(),
+        100
+    ));
+}
+
+#[test]
+fn hanging_knight() {
+    let position = Position::from_fen("4k3/8/8/3n4/8/8/3R4/4K3 w - - 0 1").unwrap();
+
+    assert_eq!(
+        hanging_pieces(&position, Color::Black),
+        1 << parse_square("d5").unwrap()
+    );
+    assert_eq!(hanging_pieces(&position, Color::White), 0);
+}
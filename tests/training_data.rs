@@ -0,0 +1,74 @@
+use chess_engine::{
+    pgn::GameResult,
+    position::Position,
+    training_data::{read_binary, read_text, write_binary, write_text, TrainingEntry},
+};
+
+// the binary form drops castling and en passant, so these have neither
+const FENS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b - - 3 17",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 99 250",
+    "8/8/8/8/8/8/6k1/4K2Q b - - 0 71",
+];
+
+fn entries(fens: &[&str]) -> Vec<TrainingEntry> {
+    let results = [GameResult::WhiteWin, GameResult::Draw, GameResult::BlackWin];
+    let scores = [0, -123, i16::MAX, i16::MIN];
+
+    fens.iter()
+        .enumerate()
+        .map(|(index, fen)| TrainingEntry {
+            position: Position::from_fen(fen).unwrap(),
+            score: scores[index % scores.len()],
+            result: results[index % results.len()],
+        })
+        .collect()
+}
+
+fn assert_same(read_back: &[TrainingEntry], written: &[TrainingEntry]) {
+    assert_eq!(read_back.len(), written.len());
+
+    for (read_back, written) in read_back.iter().zip(written) {
+        assert_eq!(read_back.position.to_fen(), written.position.to_fen());
+        assert_eq!(read_back.position.zobrist_key, written.position.zobrist_key);
+        assert_eq!(read_back.score, written.score);
+        assert_eq!(read_back.result, written.result);
+    }
+}
+
+#[test]
+fn binary_round_trip() {
+    let written = entries(&FENS);
+
+    let mut bytes = Vec::new();
+    write_binary(&mut bytes, &written).unwrap();
+    assert_eq!(bytes.len(), written.len() * TrainingEntry::SIZE);
+
+    assert_same(&read_binary(bytes.as_slice()).unwrap(), &written);
+}
+
+#[test]
+fn text_round_trip() {
+    // text goes through a fen, so castling and en passant survive too
+    let mut fens = FENS.to_vec();
+    fens.push("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3");
+    let written = entries(&fens);
+
+    let mut text = Vec::new();
+    write_text(&mut text, &written).unwrap();
+
+    assert_same(&read_text(text.as_slice()).unwrap(), &written);
+}
+
+#[test]
+fn binary_keeps_only_the_board() {
+    let fen = "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3";
+    let written = entries(&[fen]);
+
+    let read_back = TrainingEntry::from_bytes(&written[0].to_bytes()).unwrap();
+    assert_eq!(
+        read_back.position.to_fen(),
+        "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b - - 0 3"
+    );
+}